@@ -0,0 +1,17 @@
+use std::env;
+use std::path::PathBuf;
+
+// Generates `hc256.h` into `OUT_DIR`. The copy in `include/` is what C users
+// get, `tests/c.rs` checks it still matches.
+fn main() {
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("include");
+
+    cbindgen::generate(&dir)
+        .expect("Failed to generate C header")
+        .write_to_file(out.join("hc256.h"));
+
+    println!("cargo:rustc-env=HC256_FFI_INCLUDE={}", out.display());
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+}
@@ -0,0 +1,144 @@
+#ifndef HC256_H
+#define HC256_H
+
+/* Generated by cbindgen from hc256-ffi, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define HC256_OK 0
+
+/**
+ * A required pointer argument was null.
+ */
+#define HC256_ERR_NULL -1
+
+/**
+ * A key or IV was not 32 bytes long.
+ */
+#define HC256_ERR_LENGTH -2
+
+/**
+ * The offset does not fit in this platform's `size_t`.
+ */
+#define HC256_ERR_RANGE -3
+
+/**
+ * The cipher failed internally, it should not be used again.
+ */
+#define HC256_ERR_INTERNAL -4
+
+/**
+ * Byte-granular cipher, see `hc256::BufHc256`. Consecutive
+ * `hc256_buf_apply` calls behave like one call over all the data.
+ */
+typedef struct hc256_buf_t hc256_buf_t;
+
+/**
+ * Word-granular cipher, see `hc256::Hc256`. Bytes left over from an
+ * `hc256_apply` call whose length is not a multiple of 4 are discarded.
+ */
+typedef struct hc256_t hc256_t;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Creates a cipher, returns null if an argument is invalid.
+ *
+ * # Safety
+ *
+ * `key` and `iv` must be null or point to `key_len` and `iv_len` readable
+ * bytes.
+ */
+struct hc256_t *hc256_new(const uint8_t *key, size_t key_len, const uint8_t *iv, size_t iv_len);
+
+/**
+ * XORs keystream into `len` bytes at `data`.
+ *
+ * # Safety
+ *
+ * `cipher` must be null or come from `hc256_new` and not be freed, `data`
+ * must be null or point to `len` writable bytes.
+ */
+int hc256_apply(struct hc256_t *cipher, uint8_t *data, size_t len);
+
+/**
+ * Re-keys the cipher and moves it to byte `offset` of the new keystream.
+ * Offsets inside a word start at the following word.
+ *
+ * # Safety
+ *
+ * As for `hc256_apply`, and `key`/`iv` as for `hc256_new`.
+ */
+int hc256_seek(struct hc256_t *cipher,
+               const uint8_t *key,
+               size_t key_len,
+               const uint8_t *iv,
+               size_t iv_len,
+               uint64_t offset);
+
+/**
+ * Zeroizes and frees the cipher. Does nothing if `cipher` is null.
+ *
+ * # Safety
+ *
+ * `cipher` must be null or come from `hc256_new` and not be freed already.
+ */
+void hc256_free(struct hc256_t *cipher);
+
+/**
+ * Creates a buffered cipher, returns null if an argument is invalid.
+ *
+ * # Safety
+ *
+ * As for `hc256_new`.
+ */
+struct hc256_buf_t *hc256_buf_new(const uint8_t *key,
+                                  size_t key_len,
+                                  const uint8_t *iv,
+                                  size_t iv_len);
+
+/**
+ * XORs keystream into `len` bytes at `data`.
+ *
+ * # Safety
+ *
+ * `cipher` must be null or come from `hc256_buf_new` and not be freed,
+ * `data` must be null or point to `len` writable bytes.
+ */
+int hc256_buf_apply(struct hc256_buf_t *cipher, uint8_t *data, size_t len);
+
+/**
+ * Re-keys the cipher and moves it to byte `offset` of the new keystream.
+ *
+ * # Safety
+ *
+ * As for `hc256_buf_apply`, and `key`/`iv` as for `hc256_new`.
+ */
+int hc256_buf_seek(struct hc256_buf_t *cipher,
+                   const uint8_t *key,
+                   size_t key_len,
+                   const uint8_t *iv,
+                   size_t iv_len,
+                   uint64_t offset);
+
+/**
+ * Zeroizes and frees the cipher. Does nothing if `cipher` is null.
+ *
+ * # Safety
+ *
+ * `cipher` must be null or come from `hc256_buf_new` and not be freed
+ * already.
+ */
+void hc256_buf_free(struct hc256_buf_t *cipher);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* HC256_H */
@@ -0,0 +1,183 @@
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+
+pub const HC256_OK: c_int = 0;
+/// A required pointer argument was null.
+pub const HC256_ERR_NULL: c_int = -1;
+/// A key or IV was not 32 bytes long.
+pub const HC256_ERR_LENGTH: c_int = -2;
+/// The offset does not fit in this platform's `size_t`.
+pub const HC256_ERR_RANGE: c_int = -3;
+/// The cipher failed internally, it should not be used again.
+pub const HC256_ERR_INTERNAL: c_int = -4;
+
+/// Word-granular cipher, see `hc256::Hc256`. Bytes left over from an
+/// `hc256_apply` call whose length is not a multiple of 4 are discarded.
+pub struct Hc256(Box<hc256::Hc256>);
+
+/// Byte-granular cipher, see `hc256::BufHc256`. Consecutive
+/// `hc256_buf_apply` calls behave like one call over all the data.
+pub struct BufHc256(Box<hc256::BufHc256>);
+
+/// Creates a cipher, returns null if an argument is invalid.
+///
+/// # Safety
+///
+/// `key` and `iv` must be null or point to `key_len` and `iv_len` readable
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_new(key: *const u8, key_len: usize, iv: *const u8, iv_len: usize) -> *mut Hc256 {
+    let (k, iv) = match (array(key, key_len), array(iv, iv_len)) {
+        (Ok(k), Ok(iv)) => (k, iv),
+        _ => return ptr::null_mut(),
+    };
+
+    catch_unwind(|| Box::into_raw(Box::new(Hc256(hc256::Hc256::new_boxed(k, iv))))).unwrap_or(ptr::null_mut())
+}
+
+/// XORs keystream into `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `cipher` must be null or come from `hc256_new` and not be freed, `data`
+/// must be null or point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_apply(cipher: *mut Hc256, data: *mut u8, len: usize) -> c_int {
+    let (cipher, data) = match (cipher.as_mut(), buffer(data, len)) {
+        (Some(cipher), Ok(data)) => (cipher, data),
+        _ => return HC256_ERR_NULL,
+    };
+
+    guard(|| cipher.0.apply_stream(data))
+}
+
+/// Re-keys the cipher and moves it to byte `offset` of the new keystream.
+/// Offsets inside a word start at the following word.
+///
+/// # Safety
+///
+/// As for `hc256_apply`, and `key`/`iv` as for `hc256_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_seek(cipher: *mut Hc256, key: *const u8, key_len: usize, iv: *const u8, iv_len: usize, offset: u64) -> c_int {
+    let cipher = match cipher.as_mut() {
+        Some(cipher) => cipher,
+        None => return HC256_ERR_NULL,
+    };
+    let (k, iv, offset) = match seek_args(key, key_len, iv, iv_len, offset) {
+        Ok(args) => args,
+        Err(status) => return status,
+    };
+
+    guard(|| cipher.0.set_state(k, iv, offset))
+}
+
+/// Zeroizes and frees the cipher. Does nothing if `cipher` is null.
+///
+/// # Safety
+///
+/// `cipher` must be null or come from `hc256_new` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_free(cipher: *mut Hc256) {
+    if !cipher.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(cipher))));
+    }
+}
+
+/// Creates a buffered cipher, returns null if an argument is invalid.
+///
+/// # Safety
+///
+/// As for `hc256_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_buf_new(key: *const u8, key_len: usize, iv: *const u8, iv_len: usize) -> *mut BufHc256 {
+    let (k, iv) = match (array(key, key_len), array(iv, iv_len)) {
+        (Ok(k), Ok(iv)) => (k, iv),
+        _ => return ptr::null_mut(),
+    };
+
+    catch_unwind(|| Box::into_raw(Box::new(BufHc256(hc256::BufHc256::new_boxed(k, iv))))).unwrap_or(ptr::null_mut())
+}
+
+/// XORs keystream into `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `cipher` must be null or come from `hc256_buf_new` and not be freed,
+/// `data` must be null or point to `len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_buf_apply(cipher: *mut BufHc256, data: *mut u8, len: usize) -> c_int {
+    let (cipher, data) = match (cipher.as_mut(), buffer(data, len)) {
+        (Some(cipher), Ok(data)) => (cipher, data),
+        _ => return HC256_ERR_NULL,
+    };
+
+    guard(|| cipher.0.apply_stream(data))
+}
+
+/// Re-keys the cipher and moves it to byte `offset` of the new keystream.
+///
+/// # Safety
+///
+/// As for `hc256_buf_apply`, and `key`/`iv` as for `hc256_new`.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_buf_seek(cipher: *mut BufHc256, key: *const u8, key_len: usize, iv: *const u8, iv_len: usize, offset: u64) -> c_int {
+    let cipher = match cipher.as_mut() {
+        Some(cipher) => cipher,
+        None => return HC256_ERR_NULL,
+    };
+    let (k, iv, offset) = match seek_args(key, key_len, iv, iv_len, offset) {
+        Ok(args) => args,
+        Err(status) => return status,
+    };
+
+    guard(|| cipher.0.set_state(k, iv, offset))
+}
+
+/// Zeroizes and frees the cipher. Does nothing if `cipher` is null.
+///
+/// # Safety
+///
+/// `cipher` must be null or come from `hc256_buf_new` and not be freed
+/// already.
+#[no_mangle]
+pub unsafe extern "C" fn hc256_buf_free(cipher: *mut BufHc256) {
+    if !cipher.is_null() {
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(cipher))));
+    }
+}
+
+// Runs `f`, turning a panic into a status instead of unwinding into C.
+fn guard<F: FnOnce()>(f: F) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(()) => HC256_OK,
+        Err(_) => HC256_ERR_INTERNAL,
+    }
+}
+
+unsafe fn array<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8; 32], c_int> {
+    if ptr.is_null() {
+        Err(HC256_ERR_NULL)
+    } else if len != 32 {
+        Err(HC256_ERR_LENGTH)
+    } else {
+        Ok(&*(ptr as *const [u8; 32]))
+    }
+}
+
+// A null `data` is fine as long as there is nothing to process.
+unsafe fn buffer<'a>(data: *mut u8, len: usize) -> Result<&'a mut [u8], c_int> {
+    match (data.is_null(), len) {
+        (true, 0) => Ok(&mut []),
+        (true, _) => Err(HC256_ERR_NULL),
+        (false, _) => Ok(slice::from_raw_parts_mut(data, len)),
+    }
+}
+
+unsafe fn seek_args<'a>(key: *const u8, key_len: usize, iv: *const u8, iv_len: usize, offset: u64) -> Result<(&'a [u8; 32], &'a [u8; 32], usize), c_int> {
+    let k = array(key, key_len)?;
+    let iv = array(iv, iv_len)?;
+    let offset = usize::try_from(offset).map_err(|_| HC256_ERR_RANGE)?;
+    Ok((k, iv, offset))
+}
@@ -0,0 +1,66 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// The header `build.rs` generated for this build.
+const INCLUDE: &str = env!("HC256_FFI_INCLUDE");
+
+// What the Rust standard library needs from the system when linked
+// statically, per `rustc --print native-static-libs`.
+#[cfg(target_os = "linux")]
+const NATIVE_LIBS: &[&str] = &["-lpthread", "-ldl", "-lm"];
+#[cfg(target_os = "macos")]
+const NATIVE_LIBS: &[&str] = &["-lSystem", "-lm"];
+
+// `cargo test` only builds the rlib, so build the static library for the
+// same profile into the same target directory first.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn static_lib() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    let dir = exe.parent().unwrap().parent().unwrap();
+    let profile = match dir.file_name().unwrap().to_str().unwrap() {
+        "debug" => "dev",
+        other => other,
+    };
+
+    let status = Command::new(env::var("CARGO").unwrap_or_else(|_| "cargo".into()))
+        .args(["build", "-p", "hc256-ffi", "--lib", "--profile", profile, "--target-dir"])
+        .arg(dir.parent().unwrap())
+        .status()
+        .expect("Failed to run cargo");
+    assert!(status.success(), "Failed to build the static library");
+
+    dir.join("libhc256_ffi.a")
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+#[test]
+fn c_program() {
+    let manifest = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("hc256_test");
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".into()))
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(INCLUDE)
+        .arg(manifest.join("tests").join("c").join("hc256_test.c"))
+        .arg(static_lib())
+        .args(NATIVE_LIBS)
+        .arg("-o")
+        .arg(&exe)
+        .status()
+        .expect("Failed to run the C compiler");
+    assert!(status.success(), "C test program failed to compile");
+
+    let output = Command::new(&exe).output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn checked_in_header_is_current() {
+    let generated = fs::read_to_string(Path::new(INCLUDE).join("hc256.h")).unwrap();
+    let checked_in = fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("include").join("hc256.h")).unwrap();
+    assert!(generated == checked_in, "include/hc256.h is stale, copy it from {}", INCLUDE);
+}
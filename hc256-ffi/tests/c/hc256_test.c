@@ -0,0 +1,75 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "hc256.h"
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,     \
+                    __LINE__, #cond);                                  \
+            return 1;                                                  \
+        }                                                              \
+    } while (0)
+
+static const uint8_t VECTOR_1[32] = {
+    0x5b, 0x07, 0x89, 0x85, 0xd8, 0xf6, 0xf3, 0x0d,
+    0x42, 0xc5, 0xc0, 0x2f, 0xa6, 0xb6, 0x79, 0x51,
+    0x53, 0xf0, 0x65, 0x34, 0x80, 0x1f, 0x89, 0xf2,
+    0x4e, 0x74, 0x24, 0x8b, 0x72, 0x0b, 0x48, 0x18,
+};
+
+int main(void) {
+    uint8_t key[32] = {0};
+    uint8_t iv[32] = {0};
+    uint8_t other[32];
+    uint8_t out[32];
+
+    memset(other, 0x55, sizeof(other));
+
+    /* Known answer. */
+    hc256_t *cipher = hc256_new(key, sizeof(key), iv, sizeof(iv));
+    CHECK(cipher != NULL);
+    memset(out, 0, sizeof(out));
+    CHECK(hc256_apply(cipher, out, sizeof(out)) == HC256_OK);
+    CHECK(memcmp(out, VECTOR_1, sizeof(out)) == 0);
+
+    /* Seeking re-keys and skips whole words. */
+    CHECK(hc256_seek(cipher, other, 32, other, 32, 0) == HC256_OK);
+    CHECK(hc256_seek(cipher, key, 32, iv, 32, 8) == HC256_OK);
+    memset(out, 0, sizeof(out));
+    CHECK(hc256_apply(cipher, out, 8) == HC256_OK);
+    CHECK(memcmp(out, VECTOR_1 + 8, 8) == 0);
+    hc256_free(cipher);
+
+    /* The buffered cipher carries bytes across calls and seeks mid-word. */
+    hc256_buf_t *buf = hc256_buf_new(key, sizeof(key), iv, sizeof(iv));
+    CHECK(buf != NULL);
+    memset(out, 0, sizeof(out));
+    CHECK(hc256_buf_apply(buf, out, 3) == HC256_OK);
+    CHECK(hc256_buf_apply(buf, out + 3, 10) == HC256_OK);
+    CHECK(hc256_buf_apply(buf, out + 13, 19) == HC256_OK);
+    CHECK(memcmp(out, VECTOR_1, sizeof(out)) == 0);
+
+    CHECK(hc256_buf_seek(buf, key, 32, iv, 32, 5) == HC256_OK);
+    memset(out, 0, sizeof(out));
+    CHECK(hc256_buf_apply(buf, out, 27) == HC256_OK);
+    CHECK(memcmp(out, VECTOR_1 + 5, 27) == 0);
+
+    /* Bad arguments are reported, not crashed on. */
+    CHECK(hc256_buf_apply(buf, NULL, 0) == HC256_OK);
+    CHECK(hc256_buf_apply(buf, NULL, 1) == HC256_ERR_NULL);
+    CHECK(hc256_buf_apply(NULL, out, 1) == HC256_ERR_NULL);
+    CHECK(hc256_buf_seek(buf, key, 16, iv, 32, 0) == HC256_ERR_LENGTH);
+    CHECK(hc256_buf_seek(buf, key, 32, NULL, 32, 0) == HC256_ERR_NULL);
+    hc256_buf_free(buf);
+
+    CHECK(hc256_new(key, 31, iv, 32) == NULL);
+    CHECK(hc256_new(NULL, 32, iv, 32) == NULL);
+    CHECK(hc256_buf_new(key, 32, iv, 33) == NULL);
+    CHECK(hc256_seek(NULL, key, 32, iv, 32, 0) == HC256_ERR_NULL);
+    hc256_free(NULL);
+    hc256_buf_free(NULL);
+
+    return 0;
+}
@@ -0,0 +1,322 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use hc256::BufHc256;
+
+use crate::filter::{pipe, same_file, Failure};
+
+pub const MAGIC: [u8; 8] = *b"HC256BT\x01";
+pub const NONCE_LEN: usize = 32;
+pub const HEADER_LEN: usize = MAGIC.len() + NONCE_LEN;
+pub const DEFAULT_SUFFIX: &str = ".hc256";
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("batch")
+        .about("Encrypts or decrypts many files, each under its own random IV")
+        .after_help(
+            "Each output starts with a header holding a random nonce that is mixed into <IV>, so files never share keystream. \
+             Symbolic links and special files are skipped.",
+        )
+        .arg(
+            Arg::with_name("paths")
+                .value_name("PATH")
+                .help("Files, or directories with --recursive")
+                .multiple(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("recursive")
+                .short("r")
+                .long("recursive")
+                .help("Descend into directories")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("decrypt")
+                .short("d")
+                .long("decrypt")
+                .help("Decrypt files written by this command")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("suffix")
+                .long("suffix")
+                .value_name("SUFFIX")
+                .help("Added to encrypted file names and removed when decrypting, defaults to .hc256")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("mirror")
+                .long("mirror")
+                .value_name("DIR")
+                .help("Write outputs under <DIR>, keeping each path's layout, instead of next to the inputs")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Overwrite existing output files")
+                .takes_value(false),
+        )
+}
+
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32]) {
+    let options = Options {
+        decrypt: matches.is_present("decrypt"),
+        recursive: matches.is_present("recursive"),
+        force: matches.is_present("force"),
+        suffix: matches.value_of("suffix").unwrap_or(DEFAULT_SUFFIX).to_string(),
+        mirror: matches.value_of("mirror").map(PathBuf::from),
+    };
+    if options.suffix.is_empty() && options.mirror.is_none() {
+        eprintln!("<SUFFIX> must not be empty unless --mirror is used");
+        exit(1);
+    }
+
+    let roots: Vec<&Path> = matches.values_of("paths").unwrap().map(Path::new).collect();
+    let summary = process_paths(&roots, &options, key, iv, |path, outcome| match outcome {
+        Outcome::Done(_) => {}
+        Outcome::Skipped(reason) => eprintln!("{}: skipped, {}", path.display(), reason),
+        Outcome::Failed(e) => eprintln!("{}: {}", path.display(), e),
+    });
+
+    eprintln!(
+        "{} files {} ({} bytes), {} skipped, {} failed",
+        summary.done,
+        if options.decrypt { "decrypted" } else { "encrypted" },
+        summary.bytes,
+        summary.skipped,
+        summary.failed
+    );
+    if summary.failed > 0 {
+        exit(1);
+    }
+}
+
+/// How [`process_paths`] finds, names and writes its outputs.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub decrypt: bool,
+    pub recursive: bool,
+    pub force: bool,
+    /// Added when encrypting, removed when decrypting.
+    pub suffix: String,
+    /// Directory the outputs are written under instead of next to the inputs.
+    pub mirror: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { decrypt: false, recursive: false, force: false, suffix: DEFAULT_SUFFIX.to_string(), mirror: None }
+    }
+}
+
+/// What happened to one path.
+#[derive(Debug)]
+pub enum Outcome {
+    /// Processed, with the number of plaintext bytes.
+    Done(u64),
+    Skipped(&'static str),
+    Failed(io::Error),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub done: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    /// Plaintext bytes over all processed files.
+    pub bytes: u64,
+}
+
+impl Summary {
+    fn add(&mut self, outcome: &Outcome) {
+        match outcome {
+            Outcome::Done(bytes) => {
+                self.done += 1;
+                self.bytes += bytes;
+            }
+            Outcome::Skipped(_) => self.skipped += 1,
+            Outcome::Failed(_) => self.failed += 1,
+        }
+    }
+}
+
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+}
+
+/// Encrypts or decrypts every file under `roots`, carrying on past files
+/// that fail. `report` sees each path's outcome as it is decided.
+pub fn process_paths<F: FnMut(&Path, &Outcome)>(
+    roots: &[&Path],
+    options: &Options,
+    key: &[u8; 32],
+    iv: &[u8; 32],
+    mut report: F,
+) -> Summary {
+    let mut summary = Summary::default();
+    let mut record = |path: &Path, outcome: Outcome| {
+        report(path, &outcome);
+        summary.add(&outcome);
+    };
+
+    let mut jobs = Vec::new();
+    // Outputs may land inside the inputs, so the whole list is built first.
+    for root in roots {
+        collect(root, PathBuf::new(), true, options, &mut jobs, &mut record);
+    }
+
+    for job in jobs {
+        let outcome = match process(&job, options, key, iv) {
+            Ok(bytes) => Outcome::Done(bytes),
+            Err(e) => Outcome::Failed(e),
+        };
+        record(&job.input, outcome);
+    }
+
+    summary
+}
+
+// `rel` is where `path`'s children go under the mirror directory.
+fn collect<F: FnMut(&Path, Outcome)>(path: &Path, rel: PathBuf, root: bool, options: &Options, jobs: &mut Vec<Job>, record: &mut F) {
+    let file_type = match fs::symlink_metadata(path) {
+        Ok(meta) => meta.file_type(),
+        Err(e) => return record(path, Outcome::Failed(e)),
+    };
+
+    if file_type.is_symlink() {
+        record(path, Outcome::Skipped("symbolic link"));
+    } else if file_type.is_dir() {
+        if !options.recursive {
+            return record(path, Outcome::Skipped("directory without --recursive"));
+        }
+        if options.mirror.as_deref().is_some_and(|mirror| same_file(mirror, path)) {
+            return;
+        }
+
+        // A root keeps its own name under the mirror so several roots do not
+        // collide, resolved first so `.` or `..` cannot step outside it.
+        let rel = match root {
+            true => match fs::canonicalize(path) {
+                Ok(full) => full.file_name().map(PathBuf::from).unwrap_or_default(),
+                Err(e) => return record(path, Outcome::Failed(e)),
+            },
+            false => rel,
+        };
+
+        let mut entries = match fs::read_dir(path).and_then(|dir| dir.map(|e| e.map(|e| e.path())).collect::<io::Result<Vec<_>>>()) {
+            Ok(entries) => entries,
+            Err(e) => return record(path, Outcome::Failed(e)),
+        };
+        entries.sort();
+        for entry in entries {
+            let child = rel.join(entry.file_name().unwrap());
+            collect(&entry, child, false, options, jobs, record);
+        }
+    } else if file_type.is_file() {
+        match output_path(path, &rel, options) {
+            Ok(output) => jobs.push(Job { input: path.to_path_buf(), output }),
+            Err(reason) => record(path, Outcome::Skipped(reason)),
+        }
+    } else {
+        record(path, Outcome::Skipped("not a regular file"));
+    }
+}
+
+// Maps an input to its output, or says why it is left alone. `rel` is the
+// input's path below the mirror directory, including its own name.
+fn output_path(path: &Path, rel: &Path, options: &Options) -> Result<PathBuf, &'static str> {
+    let name = path.file_name().unwrap().to_string_lossy();
+    let suffix = options.suffix.as_str();
+
+    let new_name: OsString = if options.decrypt {
+        match name.strip_suffix(suffix) {
+            Some(stem) if !stem.is_empty() => stem.into(),
+            _ if options.mirror.is_some() => path.file_name().unwrap().into(),
+            _ => return Err("no suffix to remove"),
+        }
+    } else if options.mirror.is_none() && name.ends_with(suffix) {
+        return Err("already has the encrypted suffix");
+    } else {
+        let mut new_name = path.file_name().unwrap().to_os_string();
+        new_name.push(suffix);
+        new_name
+    };
+
+    match &options.mirror {
+        Some(mirror) => {
+            let dir = rel.parent().unwrap_or(Path::new(""));
+            if !dir.components().all(|c| matches!(c, Component::Normal(_))) {
+                return Err("output would be outside the mirror directory");
+            }
+            Ok(mirror.join(dir).join(new_name))
+        }
+        None => Ok(path.with_file_name(new_name)),
+    }
+}
+
+fn process(job: &Job, options: &Options, key: &[u8; 32], iv: &[u8; 32]) -> io::Result<u64> {
+    if same_file(&job.input, &job.output) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "output would overwrite the input"));
+    }
+    if let Some(dir) = job.output.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut input = File::open(&job.input)?;
+
+    let mut open = OpenOptions::new();
+    open.write(true);
+    if options.force {
+        open.create(true).truncate(true);
+    } else {
+        open.create_new(true);
+    }
+    let mut output = open.open(&job.output).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => io::Error::new(e.kind(), format!("{} exists, use --force to overwrite", job.output.display())),
+        _ => e,
+    })?;
+
+    let result = transform(&mut input, &mut output, options.decrypt, key, iv);
+    if result.is_err() {
+        drop(output);
+        let _ = fs::remove_file(&job.output);
+    }
+    result
+}
+
+// Returns the number of plaintext bytes.
+fn transform(input: &mut File, output: &mut File, decrypt: bool, key: &[u8; 32], iv: &[u8; 32]) -> io::Result<u64> {
+    let mut header: [u8; HEADER_LEN] = [0; HEADER_LEN];
+    if decrypt {
+        input.read_exact(&mut header).map_err(|_| invalid())?;
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(invalid());
+        }
+    } else {
+        header[..MAGIC.len()].copy_from_slice(&MAGIC);
+        getrandom::getrandom(&mut header[MAGIC.len()..])?;
+        output.write_all(&header)?;
+    }
+
+    let mut file_iv = *iv;
+    for (b, n) in file_iv.iter_mut().zip(&header[MAGIC.len()..]) {
+        *b ^= n;
+    }
+
+    let mut cipher = BufHc256::new_boxed(key, &file_iv);
+    pipe(&mut cipher, input, output).map_err(|e| match e {
+        Failure::Read(e) | Failure::Write(e) => e,
+    })
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "not a file written by hc256-util batch")
+}
@@ -0,0 +1,126 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use zeroize::Zeroizing;
+
+/// How binary values are written as, or read from, text.
+///
+/// Decoding any base64 variant accepts both alphabets, with or without
+/// padding, so the variants only matter for output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Raw,
+    Hex,
+    Base64,
+    Base64NoPad,
+    Base64Url,
+    Base64UrlNoPad,
+}
+
+/// Why text could not be decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    InvalidBase64(String),
+    /// Byte offset of the first character that is not a hex digit, or the
+    /// length if there is an odd number of digits.
+    InvalidHex(usize),
+}
+
+impl Encoding {
+    /// Names accepted by `from_str`, in the order of the variants.
+    pub const NAMES: [&'static str; 6] = ["raw", "hex", "base64", "base64-nopad", "base64url", "base64url-nopad"];
+
+    pub fn is_text(self) -> bool {
+        self != Encoding::Raw
+    }
+
+    pub fn encode(self, bytes: &[u8]) -> Zeroizing<Vec<u8>> {
+        let text = match self {
+            Encoding::Raw => return Zeroizing::new(bytes.to_vec()),
+            Encoding::Hex => {
+                // Built in place so no copy of the digits is left unzeroized.
+                let mut text = Zeroizing::new(Vec::with_capacity(2 * bytes.len()));
+                for byte in bytes {
+                    text.push(char::from_digit((byte >> 4) as u32, 16).unwrap() as u8);
+                    text.push(char::from_digit((byte & 0xf) as u32, 16).unwrap() as u8);
+                }
+                return text;
+            }
+            Encoding::Base64 => base64::encode_config(bytes, base64::STANDARD),
+            Encoding::Base64NoPad => base64::encode_config(bytes, base64::STANDARD_NO_PAD),
+            Encoding::Base64Url => base64::encode_config(bytes, base64::URL_SAFE),
+            Encoding::Base64UrlNoPad => base64::encode_config(bytes, base64::URL_SAFE_NO_PAD),
+        };
+        Zeroizing::new(text.into_bytes())
+    }
+
+    /// Decodes `data`, ignoring surrounding whitespace for text encodings.
+    pub fn decode(self, data: &[u8]) -> Result<Zeroizing<Vec<u8>>, DecodeError> {
+        let text = data.trim_ascii();
+        match self {
+            Encoding::Raw => Ok(Zeroizing::new(data.to_vec())),
+            Encoding::Hex => decode_hex(text),
+            _ => decode_base64(text),
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Encoding, String> {
+        const VARIANTS: [Encoding; 6] =
+            [Encoding::Raw, Encoding::Hex, Encoding::Base64, Encoding::Base64NoPad, Encoding::Base64Url, Encoding::Base64UrlNoPad];
+
+        match Encoding::NAMES.iter().position(|name| *name == s) {
+            Some(i) => Ok(VARIANTS[i]),
+            None => Err(format!("unknown encoding '{}', expected one of {}", s, Encoding::NAMES.join(", "))),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::InvalidBase64(e) => write!(f, "invalid base64: {}", e),
+            DecodeError::InvalidHex(at) => write!(f, "invalid hex at character {}", at),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+fn decode_hex(text: &[u8]) -> Result<Zeroizing<Vec<u8>>, DecodeError> {
+    if let Some(at) = text.iter().position(|b| !b.is_ascii_hexdigit()) {
+        return Err(DecodeError::InvalidHex(at));
+    }
+    if !text.len().is_multiple_of(2) {
+        return Err(DecodeError::InvalidHex(text.len()));
+    }
+
+    let digit = |b: u8| (b as char).to_digit(16).unwrap() as u8;
+    Ok(Zeroizing::new(text.chunks(2).map(|pair| digit(pair[0]) << 4 | digit(pair[1])).collect()))
+}
+
+// Maps the URL-safe alphabet onto the standard one and drops padding.
+fn decode_base64(text: &[u8]) -> Result<Zeroizing<Vec<u8>>, DecodeError> {
+    let unpadded = match text.iter().rposition(|&b| b != b'=') {
+        Some(end) => &text[..=end],
+        None => &text[..0],
+    };
+    let normalized: Zeroizing<Vec<u8>> = Zeroizing::new(
+        unpadded
+            .iter()
+            .map(|&b| match b {
+                b'-' => b'+',
+                b'_' => b'/',
+                b => b,
+            })
+            .collect(),
+    );
+
+    base64::decode_config(&normalized[..], base64::STANDARD_NO_PAD)
+        .map(Zeroizing::new)
+        .map_err(|e| DecodeError::InvalidBase64(e.to_string()))
+}
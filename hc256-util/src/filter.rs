@@ -0,0 +1,136 @@
+use std::fs::{self, File};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::Path;
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use zeroize::Zeroize;
+
+use hc256::BufHc256;
+
+// Bytes read, encrypted and written per step.
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Exit status of a process killed by SIGPIPE, what a shell reports for the
+/// usual filters when the reader goes away.
+pub const BROKEN_PIPE_STATUS: i32 = 128 + 13;
+
+pub fn subcommand<'a, 'b>(name: &'a str, about: &'a str) -> App<'a, 'b> {
+    SubCommand::with_name(name)
+        .about(about)
+        .arg(
+            Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("File to read, standard input if missing or -")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output file")
+                .short("o")
+                .long("output")
+                .value_name("OUTPUT FILE")
+                .help("File to write, standard output if missing or -")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Write ciphertext even if standard output is a terminal")
+                .takes_value(false),
+        )
+}
+
+/// Streams `matches`' input to its output through a `BufHc256`. Only
+/// `encrypt` refuses to write to a terminal, decrypted output may be text.
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32], encrypt: bool) {
+    let input = matches.value_of("file").filter(|f| *f != "-");
+    let output = matches.value_of("output file").filter(|f| *f != "-");
+
+    if encrypt && output.is_none() && !matches.is_present("force") && io::stdout().is_terminal() {
+        eprintln!("Refusing to write ciphertext to a terminal, redirect it or use --force");
+        exit(1);
+    }
+    if let (Some(input), Some(output)) = (input, output) {
+        // Creating the output would truncate the input before it is read.
+        if same_file(Path::new(input), Path::new(output)) {
+            eprintln!("{}: input and output are the same file", output);
+            exit(1);
+        }
+    }
+
+    let reader: Box<dyn Read> = match input {
+        Some(path) => Box::new(File::open(path).unwrap_or_else(|e| fail(path, e))),
+        None => Box::new(io::stdin().lock()),
+    };
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path).unwrap_or_else(|e| fail(path, e))),
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let mut cipher = BufHc256::new_boxed(key, iv);
+    match pipe(&mut cipher, reader, writer) {
+        Ok(_) => {}
+        Err(Failure::Write(e)) if e.kind() == io::ErrorKind::BrokenPipe => exit(BROKEN_PIPE_STATUS),
+        Err(Failure::Read(e)) => fail(input.unwrap_or("stdin"), e),
+        Err(Failure::Write(e)) => fail(output.unwrap_or("stdout"), e),
+    }
+}
+
+/// Which side of a [`pipe`] failed.
+#[derive(Debug)]
+pub enum Failure {
+    Read(io::Error),
+    Write(io::Error),
+}
+
+/// Copies `reader` to `writer` through `cipher` until EOF, then flushes.
+/// Returns the number of bytes copied.
+pub fn pipe<R: Read, W: Write>(cipher: &mut BufHc256, mut reader: R, mut writer: W) -> Result<u64, Failure> {
+    let mut buf = vec![0; BUFFER_SIZE];
+    let mut total = 0;
+
+    let result = loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => break writer.flush().map(|()| total).map_err(Failure::Write),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => break Err(Failure::Read(e)),
+        };
+
+        cipher.apply_stream(&mut buf[..n]);
+        if let Err(e) = writer.write_all(&buf[..n]) {
+            break Err(Failure::Write(e));
+        }
+        total += n as u64;
+    };
+
+    buf.zeroize();
+    result
+}
+
+/// Whether both paths exist and name the same file, hard links included.
+#[cfg(unix)]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// Whether both paths exist and resolve to the same file.
+#[cfg(not(unix))]
+pub fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn fail(what: &str, e: io::Error) -> ! {
+    eprintln!("{}: {}", what, e);
+    exit(1);
+}
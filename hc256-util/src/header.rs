@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use hc256::{parallel, seekable};
+
+use crate::output_encoding;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("header")
+        .about("Shows the header of a file written by the parallel or seekable commands")
+        .arg(
+            Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("File to inspect")
+                .takes_value(true)
+                .required(true),
+        )
+}
+
+pub fn run(matches: &ArgMatches) {
+    let filename = matches.value_of("file").unwrap();
+    let encoding = output_encoding(matches, false);
+
+    // Both formats use the same header length.
+    let mut bytes: [u8; seekable::HEADER_LEN] = [0; seekable::HEADER_LEN];
+    if let Err(e) = File::open(filename).and_then(|mut file| file.read_exact(&mut bytes)) {
+        eprintln!("{}: {}", filename, e);
+        exit(1);
+    }
+
+    let description = if let Ok(header) = seekable::Header::from_bytes(&bytes) {
+        format!("format: seekable\nchunk size: {}\n", header.chunk_size)
+    } else if let Ok(header) = parallel::Header::from_bytes(&bytes) {
+        format!("format: parallel\nsegment size: {}\n", header.segment_size)
+    } else {
+        eprintln!("{}: not a seekable or parallel hc256 file", filename);
+        exit(1);
+    };
+
+    let mut out = io::stdout().lock();
+    let result = out
+        .write_all(description.as_bytes())
+        .and_then(|_| out.write_all(b"header: "))
+        .and_then(|_| out.write_all(&encoding.encode(&bytes)))
+        .and_then(|_| out.write_all(b"\n"));
+    if let Err(e) = result {
+        eprintln!("stdout: {}", e);
+        exit(1);
+    }
+}
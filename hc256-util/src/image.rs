@@ -0,0 +1,125 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use hc256::sector::{SectorCipher, SectorIv};
+
+use crate::parse_arg;
+
+// Sectors read, processed and written back per batch.
+const BATCH: u64 = 2048;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("image")
+        .about("Encrypts or decrypts a range of sectors of a disk image in place")
+        .arg(
+            Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("Disk image or block device to process")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(Arg::with_name("output file")
+            .short("o")
+            .long("output")
+            .value_name("OUTPUT FILE")
+            .help("Write the processed sectors to this image at the same offsets instead"))
+        .arg(
+            Arg::with_name("sector size")
+                .long("sector-size")
+                .value_name("BYTES")
+                .help("Sector size of the image")
+                .possible_values(&["512", "4096"])
+                .default_value("512"),
+        )
+        .arg(
+            Arg::with_name("start")
+                .long("start")
+                .value_name("SECTOR")
+                .help("First sector to process")
+                .default_value("0"),
+        )
+        .arg(
+            Arg::with_name("count")
+                .long("count")
+                .value_name("SECTORS")
+                .help("Number of sectors to process, defaults to the end of the image")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("essiv")
+                .long("essiv")
+                .help("Derive sector IVs ESSIV-style instead of plain64")
+                .takes_value(false),
+        )
+}
+
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32]) {
+    let sector_size: u64 = parse_arg(matches, "sector size");
+    let start: u64 = parse_arg(matches, "start");
+    let mode = if matches.is_present("essiv") { SectorIv::Essiv } else { SectorIv::Plain64 };
+    let cipher = SectorCipher::new(key, iv, mode, sector_size as usize);
+
+    let filename = matches.value_of("file").unwrap();
+    let mut input = match matches.value_of("output file") {
+        Some(_) => File::open(filename),
+        None => OpenOptions::new().read(true).write(true).open(filename),
+    }
+    .expect("Please enter a valid file path");
+    let mut output = matches.value_of("output file").map(|p| {
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(p)
+            .expect("Failed to open output file")
+    });
+
+    let len = input.seek(SeekFrom::End(0)).expect("Failed to read file");
+    let available = len.div_ceil(sector_size).saturating_sub(start);
+    let count = match matches.value_of("count") {
+        Some(_) => parse_arg(matches, "count"),
+        None => available,
+    };
+    if count > available {
+        eprintln!("The image only has {} sectors from sector {}", available, start);
+        exit(1);
+    }
+
+    let mut buf = Vec::new();
+    let mut sector = start;
+    while sector < start + count {
+        let batch = BATCH.min(start + count - sector);
+        let offset = sector * sector_size;
+
+        buf.resize((batch * sector_size) as usize, 0);
+        input.seek(SeekFrom::Start(offset)).expect("Failed to read file");
+        let read = read_full(&mut input, &mut buf);
+        buf.truncate(read);
+
+        cipher.apply_range(sector, &mut buf);
+
+        let dest = output.as_mut().unwrap_or(&mut input);
+        dest.seek(SeekFrom::Start(offset)).expect("Failed to write content to file");
+        dest.write_all(&buf).expect("Failed to write content to file");
+
+        sector += batch;
+    }
+
+    eprintln!("Processed {} sectors starting at sector {}", count, start);
+}
+
+fn read_full(file: &mut File, buf: &mut [u8]) -> usize {
+    let mut read = 0;
+    while read < buf.len() {
+        match file.read(&mut buf[read..]).expect("Failed to read file") {
+            0 => break,
+            n => read += n,
+        }
+    }
+    read
+}
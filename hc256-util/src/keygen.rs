@@ -0,0 +1,117 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use zeroize::Zeroize;
+
+use hc256::Hc256;
+
+use crate::{encode_output, output_encoding};
+
+// Fixed IV the fingerprint keystream is drawn under.
+const FINGERPRINT_IV: [u8; 32] = *b"hc256-util fingerprint\0\0\0\0\0\0\0\0\0\0";
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("keygen")
+        .about("Generates a random key and/or initialization vector")
+        .after_help("Without <KEY OUT> or <IV OUT> a key and an initialization vector are printed, one per line. Values are written raw to files and as base64 otherwise unless --encoding is given.")
+        .arg(
+            Arg::with_name("key out")
+                .long("key-out")
+                .value_name("KEY OUT")
+                .help("File to write a new key to")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("iv out")
+                .long("iv-out")
+                .value_name("IV OUT")
+                .help("File to write a new initialization vector to")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Overwrite existing files")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("fingerprint")
+                .long("fingerprint")
+                .help("Print a short fingerprint of each value to stderr")
+                .takes_value(false),
+        )
+}
+
+pub fn run(matches: &ArgMatches) {
+    let key_out = matches.value_of("key out");
+    let iv_out = matches.value_of("iv out");
+    let to_stdout = key_out.is_none() && iv_out.is_none();
+
+    let encoding = output_encoding(matches, !to_stdout);
+
+    let items = [("key", key_out), ("iv", iv_out)];
+    for (name, out) in items {
+        if out.is_none() && !to_stdout {
+            continue;
+        }
+
+        let mut value: [u8; 32] = [0; 32];
+        if let Err(e) = getrandom::getrandom(&mut value) {
+            eprintln!("Failed to read the OS random number generator: {}", e);
+            exit(1);
+        }
+
+        let encoded = encode_output(encoding, &value);
+        let result = match out {
+            Some(path) => write_file(path, &encoded, matches.is_present("force")),
+            None => io::stdout().write_all(&encoded),
+        };
+        if let Err(e) = result {
+            eprintln!("{}: {}", out.unwrap_or("stdout"), e);
+            exit(1);
+        }
+
+        if matches.is_present("fingerprint") {
+            eprintln!("{} fingerprint: {}", name, fingerprint(&value));
+        }
+        value.zeroize();
+    }
+}
+
+/// First 8 keystream bytes under the value as key and a fixed IV, so two
+/// copies can be compared without showing either.
+fn fingerprint(value: &[u8; 32]) -> String {
+    let mut check: [u8; 8] = [0; 8];
+    Hc256::new(value, &FINGERPRINT_IV).apply_stream(&mut check);
+
+    check.chunks(2).map(|pair| format!("{:02x}{:02x}", pair[0], pair[1])).collect::<Vec<_>>().join(":")
+}
+
+// New files are created 0600, `force` truncates an existing one and
+// tightens its permissions to match.
+fn write_file(path: &str, contents: &[u8], force: bool) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        if force && fs::metadata(path).is_ok() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    let mut file = options.open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => io::Error::new(e.kind(), "file exists, use --force to overwrite"),
+        _ => e,
+    })?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
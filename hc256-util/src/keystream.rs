@@ -0,0 +1,91 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use zeroize::Zeroize;
+
+use hc256::BufHc256;
+use hc256_util::encoding::Encoding;
+
+use crate::{output_encoding, parse_arg};
+
+// Multiple of 2 and 3 so hex and base64 chunks join without padding.
+const CHUNK: usize = 48 * 1024;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("keystream")
+        .about("Writes raw keystream, for checking other implementations")
+        .arg(
+            Arg::with_name("length")
+                .short("n")
+                .long("length")
+                .value_name("BYTES")
+                .help("Number of keystream bytes to write")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("offset")
+                .long("offset")
+                .value_name("BYTES")
+                .help("Keystream offset to start from")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output file")
+                .short("o")
+                .long("output")
+                .value_name("OUTPUT FILE")
+                .help("File to write instead of standard output")
+                .takes_value(true),
+        )
+}
+
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32]) {
+    let length: u64 = parse_arg(matches, "length");
+    let offset: u64 = match matches.value_of("offset") {
+        Some(_) => parse_arg(matches, "offset"),
+        None => 0,
+    };
+    let output = matches.value_of("output file");
+    let encoding = output_encoding(matches, output.is_some());
+
+    // Skipping ahead is what `set_state` would do, without a second key setup.
+    let mut cipher = BufHc256::new_boxed(key, iv);
+    let mut buf = vec![0; CHUNK];
+    let mut left = offset;
+    while left > 0 {
+        let n = left.min(CHUNK as u64) as usize;
+        cipher.fill_keystream(&mut buf[..n]);
+        left -= n as u64;
+    }
+    buf.zeroize();
+
+    let result = match output {
+        Some(path) => File::create(path).and_then(|file| dump(&mut cipher, length, encoding, file)),
+        None => dump(&mut cipher, length, encoding, io::stdout().lock()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}: {}", output.unwrap_or("stdout"), e);
+        exit(1);
+    }
+}
+
+fn dump<W: Write>(cipher: &mut BufHc256, length: u64, encoding: Encoding, mut out: W) -> io::Result<()> {
+    let mut buf = vec![0; CHUNK];
+    let mut left = length;
+
+    while left > 0 {
+        let n = left.min(CHUNK as u64) as usize;
+        cipher.fill_keystream(&mut buf[..n]);
+        out.write_all(&encoding.encode(&buf[..n]))?;
+        left -= n as u64;
+    }
+    buf.zeroize();
+
+    if encoding.is_text() {
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
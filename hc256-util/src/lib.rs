@@ -0,0 +1,4 @@
+pub mod batch;
+pub mod encoding;
+pub mod filter;
+pub mod source;
@@ -1,10 +1,24 @@
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{self, IsTerminal, Read, Write};
 use std::process::exit;
+use std::str::FromStr;
 
-use clap::{App, Arg};
+use clap::{App, AppSettings, Arg, ArgMatches};
+
+use zeroize::Zeroizing;
 
 use hc256::Hc256;
+use hc256_util::batch;
+use hc256_util::encoding::Encoding;
+use hc256_util::filter;
+use hc256_util::source::KeySource;
+
+mod header;
+mod image;
+mod keygen;
+mod keystream;
+mod parallel;
+mod seekable;
 
 fn main() {
     let matches = App::new("Hc256 Encryption Utility")
@@ -16,63 +30,65 @@ fn main() {
                 .short("k")
                 .long("key")
                 .value_name("KEY")
-                .help("Base64 encoded 32 byte encryption key")
-                .takes_value(true),
+                .help("32 byte encryption key as b64:DATA, hex:DATA, file:PATH, env:VAR, fd:N, stdin: or a bare base64 value")
+                .takes_value(true)
+                .global(true),
         )
         .arg(
             Arg::with_name("key file")
                 .short("K")
                 .long("keyfile")
                 .value_name("KEY FILE")
-                .help("Encryption key file containing 32 raw bytes")
-                .takes_value(true),
+                .help("Encryption key file containing 32 raw bytes, same as --key file:<KEY FILE>")
+                .takes_value(true)
+                .conflicts_with("key")
+                .global(true),
         )
         .arg(
             Arg::with_name("iv")
                 .short("i")
                 .long("iv")
                 .value_name("IV")
-                .help("Base64 encoded 32 byte initialization vector")
-                .takes_value(true),
+                .help("32 byte initialization vector, accepts the same sources as <KEY>")
+                .takes_value(true)
+                .global(true),
         )
         .arg(
             Arg::with_name("iv file")
                 .short("I")
                 .long("ivfile")
                 .value_name("IV FILE")
-                .help("Initialization vector file containing raw bytes")
-                .takes_value(true),
-        )
-        .arg(
-            Arg::with_name("smart key")
-                .short("s")
-                .long("smartkey")
-                .value_name("SMART KEY")
-                .help("Attempts to base64 decode the key and if fails will try to open file, the order can be flipped with the <TOGGLE> flag")
+                .help("Initialization vector file containing 32 raw bytes, same as --iv file:<IV FILE>")
                 .takes_value(true)
+                .conflicts_with("iv")
+                .global(true),
         )
         .arg(
-            Arg::with_name("smart iv")
-                .short("S")
-                .long("smartiv")
-                .value_name("SMART IV")
-                .help("Attempts to base64 decode the initialization vector and if fails will try to open file, the order can be flipped with the <TOGGLE> flag")
+            Arg::with_name("key encoding")
+                .short("E")
+                .long("key-encoding")
+                .value_name("ENCODING")
+                .help("Encoding of bare, env:, file:, fd: and stdin: keys and IVs")
+                .possible_values(&Encoding::NAMES)
                 .takes_value(true)
+                .global(true),
         )
         .arg(
-            Arg::with_name("toggle")
-                .short("t")
-                .long("toggle")
-                .value_name("TOGGLE")
-                .help("Toggles the order of smart decoding resolution")
-                .takes_value(false)
+            Arg::with_name("encoding")
+                .short("e")
+                .long("encoding")
+                .value_name("ENCODING")
+                .help("Encoding of keygen, keystream and header output")
+                .possible_values(&Encoding::NAMES)
+                .takes_value(true)
+                .global(true),
         )
         .arg(
             Arg::with_name("file")
                 .short("f")
                 .long("file")
                 .value_name("FILE")
-                .help("File to encrypt")
+                .help("File to encrypt, - streams standard input to standard output")
                 .takes_value(true)
                 .required(true),
         )
@@ -81,127 +97,44 @@ fn main() {
             .long("output")
             .value_name("OUTPUT FILE")
             .help("Specify an output file to use instead of inplace encryption"))
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("With -f -, write ciphertext even if standard output is a terminal")
+                .takes_value(false),
+        )
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(batch::subcommand())
+        .subcommand(filter::subcommand("decrypt", "Decrypts standard input or a file as a stream"))
+        .subcommand(filter::subcommand("encrypt", "Encrypts standard input or a file as a stream"))
+        .subcommand(header::subcommand())
+        .subcommand(image::subcommand())
+        .subcommand(keygen::subcommand())
+        .subcommand(keystream::subcommand())
+        .subcommand(parallel::subcommand())
+        .subcommand(seekable::subcommand())
         .get_matches();
 
-    let key: [u8; 32] = match (
-        matches.value_of("key"),
-        matches.value_of("key file"),
-        matches.value_of("smart key"),
-        matches.occurrences_of("toggle"),
-    ) {
-        (Some(v), _, _, _) => {
-            match get_base64_val(v) {
-                Some(v) => v,
-                None => {
-                    eprintln!("You must supply either a base64 encoded key or key file");
-                    exit(1);
-                }
-            }
-        }
-        (_, Some(v), _, _) => {
-            match get_file_val(v) {
-                Some(v) => v,
-                None => {
-                    eprintln!("You must supply either a base64 encoded key or key file");
-                    exit(1);
-                }
-            }
-        }
-        (_, _, Some(v), t) => {
-            if t < 1 {
-                match get_base64_val(v) {
-                    Some(v) => v,
-                    None => {
-                        match get_file_val(v) {
-                            Some(v) => v,
-                            None => {
-                                eprintln!("You must supply either a base64 encoded key or key file");
-                                exit(1);
-                            }
-                        }
-                    }
-                }
-            } else {
-                match get_file_val(v) {
-                    Some(v) => v,
-                    None => {
-                        match get_base64_val(v) {
-                            Some(v) => v,
-                            None => {
-                                eprintln!("You must supply either a base64 encoded key or key file");
-                                exit(1);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        _ => {
-            eprintln!("You must supply either a base64 encoded key or key file");
-            exit(1);
-        }
-    };
+    match matches.subcommand() {
+        ("batch", Some(m)) => return batch::run(m, &read_key(m), &read_iv(m)),
+        ("decrypt", Some(m)) => return filter::run(m, &read_key(m), &read_iv(m), false),
+        ("encrypt", Some(m)) => return filter::run(m, &read_key(m), &read_iv(m), true),
+        ("header", Some(m)) => return header::run(m),
+        ("image", Some(m)) => return image::run(m, &read_key(m), &read_iv(m)),
+        ("keygen", Some(m)) => return keygen::run(m),
+        ("keystream", Some(m)) => return keystream::run(m, &read_key(m), &read_iv(m)),
+        ("parallel", Some(m)) => return parallel::run(m, &read_key(m), &read_iv(m)),
+        ("seekable", Some(m)) => return seekable::run(m, &read_key(m), &read_iv(m)),
+        _ => {}
+    }
 
-    let iv: [u8; 32] = match (
-        matches.value_of("iv"),
-        matches.value_of("iv file"),
-        matches.value_of("smart iv"),
-        matches.occurrences_of("toggle"),
-    ) {
-        (Some(v), _, _, _) => {
-            match get_base64_val(v) {
-                Some(v) => v,
-                None => {
-                    eprintln!("You must supply either a base64 encoded initialization vector or initialization vector file");
-                    exit(1);
-                }
-            }
-        }
-        (_, Some(v), _, _) => {
-            match get_file_val(v) {
-                Some(v) => v,
-                None => {
-                    eprintln!("You must supply either a base64 encoded initialization vector or initialization vector file");
-                    exit(1);
-                }
-            }
-        }
-        (_, _, Some(v), t) => {
-            if t < 1 {
-                match get_base64_val(v) {
-                    Some(v) => v,
-                    None => {
-                        match get_file_val(v) {
-                            Some(v) => v,
-                            None => {
-                                eprintln!("You must supply either a base64 encoded initialization vector or initialization vector file");
-                                exit(1);
-                            }
-                        }
-                    }
-                }
-            } else {
-                match get_file_val(v) {
-                    Some(v) => v,
-                    None => {
-                        match get_base64_val(v) {
-                            Some(v) => v,
-                            None => {
-                                eprintln!("You must supply either a base64 encoded initialization vector or initialization vector file");
-                                exit(1);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        _ => {
-            eprintln!("You must supply either a base64 encoded initialization vector or initialization vector file");
-            exit(1);
-        }
-    };
+    let key = read_key(&matches);
+    let iv = read_iv(&matches);
 
     let filename = matches.value_of("file").unwrap();
+    if filename == "-" {
+        return filter::run(&matches, &key, &iv, true);
+    }
     let mut file = File::open(filename).expect("Please enter a valid file path");
     let mut content = Vec::new();
     file.read_to_end(&mut content).expect("Failed to read file");
@@ -218,36 +151,67 @@ fn main() {
     .expect("Failed to write content to file");
 }
 
-fn get_base64_val(val: &str) -> Option<[u8; 32]> {
-    let dv = base64::decode(val);
+fn read_key(matches: &ArgMatches) -> Zeroizing<[u8; 32]> {
+    read_source(matches, "key", "key file", "key")
+}
 
-    let dv = match dv {
-        Ok(v) => v,
-        Err(_) => return None,
+fn read_iv(matches: &ArgMatches) -> Zeroizing<[u8; 32]> {
+    read_source(matches, "iv", "iv file", "initialization vector")
+}
+
+fn read_source(matches: &ArgMatches, arg: &str, file_arg: &str, what: &str) -> Zeroizing<[u8; 32]> {
+    let source = match (matches.value_of(arg), matches.value_of(file_arg)) {
+        (Some(v), _) => KeySource::parse(v),
+        (_, Some(path)) => Ok(KeySource::File(path.into())),
+        _ => {
+            eprintln!("Missing {}, supply it with --{} or --{}file", what, arg, arg);
+            exit(1);
+        }
     };
 
-    if dv.len() == 32 {
-        Some(dv.try_into().unwrap())
-    } else {
-        None
+    match source.and_then(|source| source.read_as(encoding_arg(matches, "key encoding"))) {
+        Ok(value) => value,
+        Err(e) => {
+            eprintln!("Invalid {}: {}", what, e);
+            exit(1);
+        }
     }
 }
 
-fn get_file_val(val: &str) -> Option<[u8; 32]> {
-    let file = File::open(val);
+fn encoding_arg(matches: &ArgMatches, name: &str) -> Option<Encoding> {
+    matches.value_of(name).map(|e| e.parse().unwrap())
+}
 
-    let mut file = match file {
-        Ok(v) => v,
-        Err(_) => return None,
+// The --encoding for tool output. Defaults to raw when writing to a file and
+// base64 otherwise, and never sends raw bytes to a terminal.
+fn output_encoding(matches: &ArgMatches, to_file: bool) -> Encoding {
+    let encoding = match encoding_arg(matches, "encoding") {
+        Some(e) => e,
+        None if to_file => Encoding::Raw,
+        None => Encoding::Base64,
     };
+    if !to_file && !encoding.is_text() && io::stdout().is_terminal() {
+        eprintln!("Refusing to write raw bytes to a terminal, choose a text --encoding");
+        exit(1);
+    }
+    encoding
+}
 
-    let mut fc = Vec::new();
+// Text encodings get a trailing newline.
+fn encode_output(encoding: Encoding, bytes: &[u8]) -> Zeroizing<Vec<u8>> {
+    let mut encoded = encoding.encode(bytes);
+    if encoding.is_text() {
+        encoded.push(b'\n');
+    }
+    encoded
+}
 
-    if file.read_to_end(&mut fc).is_err() {
-        None
-    } else if fc.len() == 32 {
-        Some(fc.try_into().unwrap())
-    } else {
-        None
+fn parse_arg<T: FromStr>(matches: &ArgMatches, name: &str) -> T {
+    match matches.value_of(name).unwrap().parse() {
+        Ok(v) => v,
+        Err(_) => {
+            eprintln!("<{}> must be a non-negative integer", name.to_uppercase());
+            exit(1);
+        }
     }
 }
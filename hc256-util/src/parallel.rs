@@ -0,0 +1,130 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use hc256::parallel::{available_threads, Header, ParallelCipher, DEFAULT_SEGMENT_SIZE, HEADER_LEN, MAX_SEGMENT_SIZE};
+
+use crate::parse_arg;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("parallel")
+        .about("Encrypts or decrypts a file as independent segments on multiple threads")
+        .arg(
+            Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("File to read")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("output file")
+                .short("o")
+                .long("output")
+                .value_name("OUTPUT FILE")
+                .help("File to write")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("decrypt")
+                .short("d")
+                .long("decrypt")
+                .help("Decrypt a file written by this command")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("segment size")
+                .long("segment-size")
+                .value_name("BYTES")
+                .help("Bytes per independently encrypted segment, at most 64MiB")
+                .takes_value(true)
+                .conflicts_with("decrypt"),
+        )
+        .arg(
+            Arg::with_name("threads")
+                .short("j")
+                .long("threads")
+                .value_name("THREADS")
+                .help("Worker threads, defaults to the number of cores")
+                .takes_value(true),
+        )
+}
+
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32]) {
+    let filename = matches.value_of("file").unwrap();
+    let threads: usize = match matches.value_of("threads") {
+        Some(_) => parse_arg(matches, "threads"),
+        None => available_threads(),
+    };
+    let segment_size: u64 = match matches.value_of("segment size") {
+        Some(_) => parse_arg(matches, "segment size"),
+        None => DEFAULT_SEGMENT_SIZE,
+    };
+    if threads == 0 || segment_size == 0 {
+        eprintln!("<THREADS> and <SEGMENT SIZE> must not be zero");
+        exit(1);
+    }
+    if segment_size > MAX_SEGMENT_SIZE {
+        eprintln!("<SEGMENT SIZE> must be at most {} bytes", MAX_SEGMENT_SIZE);
+        exit(1);
+    }
+
+    let input = File::open(filename).expect("Please enter a valid file path");
+    let output = File::create(matches.value_of("output file").unwrap()).expect("Failed to open output file");
+
+    let result = if matches.is_present("decrypt") {
+        decrypt(input, output, key, iv, threads)
+    } else {
+        encrypt(input, output, key, iv, Header::new(segment_size), threads)
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", filename, e);
+        exit(1);
+    }
+}
+
+fn encrypt(input: File, mut output: File, key: &[u8; 32], iv: &[u8; 32], header: Header, threads: usize) -> io::Result<()> {
+    output.write_all(&header.to_bytes())?;
+    process(input, output, &ParallelCipher::new(key, iv, header), threads)
+}
+
+fn decrypt(mut input: File, output: File, key: &[u8; 32], iv: &[u8; 32], threads: usize) -> io::Result<()> {
+    let mut bytes: [u8; HEADER_LEN] = [0; HEADER_LEN];
+    input.read_exact(&mut bytes)?;
+    let header = Header::from_bytes(&bytes)?;
+
+    process(input, output, &ParallelCipher::new(key, iv, header), threads)
+}
+
+// Reads one segment per thread at a time so memory stays bounded.
+fn process(mut input: File, mut output: File, cipher: &ParallelCipher, threads: usize) -> io::Result<()> {
+    let segment_size = cipher.header().segment_size;
+    let len = (segment_size as usize).saturating_mul(threads);
+    let mut buf = Vec::new();
+    buf.try_reserve_exact(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::OutOfMemory, format!("cannot buffer {} segments of {} bytes", threads, segment_size)))?;
+    buf.resize(len, 0);
+    let mut segment = 0;
+
+    loop {
+        let mut read = 0;
+        while read < buf.len() {
+            match input.read(&mut buf[read..])? {
+                0 => break,
+                n => read += n,
+            }
+        }
+        if read == 0 {
+            return Ok(());
+        }
+
+        cipher.apply_threads(segment, &mut buf[..read], threads);
+        output.write_all(&buf[..read])?;
+        segment += (read as u64).div_ceil(segment_size);
+    }
+}
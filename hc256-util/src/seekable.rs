@@ -0,0 +1,128 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::process::exit;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use hc256::seekable::{SeekableReader, SeekableWriter, DEFAULT_CHUNK_SIZE};
+
+use crate::parse_arg;
+
+pub fn subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("seekable")
+        .about("Converts a file to or from the chunked random-access format")
+        .arg(
+            Arg::with_name("file")
+                .short("f")
+                .long("file")
+                .value_name("FILE")
+                .help("File to read")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("output file")
+                .short("o")
+                .long("output")
+                .value_name("OUTPUT FILE")
+                .help("File to write")
+                .takes_value(true)
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("decrypt")
+                .short("d")
+                .long("decrypt")
+                .help("Decrypt a seekable file instead of creating one")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("append")
+                .short("a")
+                .long("append")
+                .help("Append <FILE> to an existing seekable <OUTPUT FILE>")
+                .takes_value(false)
+                .conflicts_with("decrypt"),
+        )
+        .arg(
+            Arg::with_name("chunk size")
+                .long("chunk-size")
+                .value_name("BYTES")
+                .help("Plaintext bytes per chunk of a new file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("offset")
+                .long("offset")
+                .value_name("BYTES")
+                .help("Plaintext offset to start decrypting from")
+                .requires("decrypt")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("length")
+                .long("length")
+                .value_name("BYTES")
+                .help("Number of plaintext bytes to decrypt")
+                .requires("decrypt")
+                .takes_value(true),
+        )
+}
+
+pub fn run(matches: &ArgMatches, key: &[u8; 32], iv: &[u8; 32]) {
+    let filename = matches.value_of("file").unwrap();
+    let output = matches.value_of("output file").unwrap();
+    let mut input = File::open(filename).expect("Please enter a valid file path");
+
+    let result = if matches.is_present("decrypt") {
+        decrypt(matches, input, output, key, iv)
+    } else {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(!matches.is_present("append"))
+            .open(output)
+            .expect("Failed to open output file");
+
+        let writer = if matches.is_present("append") {
+            SeekableWriter::open(file, key, iv)
+        } else {
+            let chunk_size = match matches.value_of("chunk size") {
+                Some(_) => parse_arg(matches, "chunk size"),
+                None => DEFAULT_CHUNK_SIZE,
+            };
+            if chunk_size == 0 {
+                eprintln!("<CHUNK SIZE> must not be zero");
+                exit(1);
+            }
+            SeekableWriter::create(file, key, iv, chunk_size)
+        };
+
+        writer.and_then(|mut w| {
+            io::copy(&mut input, &mut w)?;
+            w.finish().map(|_| ())
+        })
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}: {}", filename, e);
+        exit(1);
+    }
+}
+
+fn decrypt(matches: &ArgMatches, input: File, output: &str, key: &[u8; 32], iv: &[u8; 32]) -> io::Result<()> {
+    let mut reader = SeekableReader::new(input, key, iv)?;
+    let offset = match matches.value_of("offset") {
+        Some(_) => parse_arg(matches, "offset"),
+        None => 0,
+    };
+    let length = match matches.value_of("length") {
+        Some(_) => parse_arg(matches, "length"),
+        None => reader.len().saturating_sub(offset),
+    };
+
+    reader.seek(SeekFrom::Start(offset))?;
+    io::copy(&mut reader.take(length), &mut File::create(output)?)?;
+    Ok(())
+}
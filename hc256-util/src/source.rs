@@ -0,0 +1,230 @@
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use zeroize::Zeroizing;
+
+use crate::encoding::{DecodeError, Encoding};
+
+// Most bytes read from a file or stream holding a value in any encoding.
+const MAX_ENCODED: u64 = 256;
+
+/// Where a key or IV comes from, written `scheme:value` on the command line.
+///
+/// | Syntax       | Contents                                          |
+/// |--------------|---------------------------------------------------|
+/// | `b64:DATA`   | base64, standard or URL-safe, padded or not       |
+/// | `hex:DATA`   | 64 hex digits                                     |
+/// | `file:PATH`  | file holding the value, 32 raw bytes by default   |
+/// | `env:VAR`    | environment variable holding base64 by default    |
+/// | `fd:N`       | read from open file descriptor `N`, like `file:`  |
+/// | `stdin:`     | read from standard input, like `file:`            |
+///
+/// A bare value without a scheme is read like `env:` contents. `read_as`
+/// changes the encoding expected of bare values, variables, files and
+/// streams.
+///
+/// `fd:` borrows the descriptor and leaves it open. Regular files are read
+/// from the start, so one descriptor can supply both the key and the IV.
+/// Descriptors 0 to 2 are refused, standard input is read with `stdin:`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum KeySource {
+    Encoded(Encoding, Zeroizing<String>),
+    Bare(Zeroizing<String>),
+    File(PathBuf),
+    Env(String),
+    Fd(i32),
+    Stdin,
+}
+
+/// Why a [`KeySource`] could not be parsed or read.
+#[derive(Debug)]
+pub enum SourceError {
+    UnknownScheme(String),
+    InvalidFd(String),
+    /// `fd:` named standard input, output or error.
+    StdioFd(i32),
+    Decode(DecodeError),
+    EnvNotSet(String),
+    EnvNotUnicode(String),
+    /// Decoded or read length when it was not 32 bytes.
+    WrongLength(usize),
+    /// A file or stream went on past what any encoding of 32 bytes needs.
+    TooLong,
+    Io(String, io::Error),
+}
+
+impl fmt::Debug for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySource::Encoded(encoding, _) => f.debug_tuple("Encoded").field(encoding).finish_non_exhaustive(),
+            KeySource::Bare(_) => f.write_str("Bare(..)"),
+            KeySource::File(path) => f.debug_tuple("File").field(path).finish(),
+            KeySource::Env(var) => f.debug_tuple("Env").field(var).finish(),
+            KeySource::Fd(fd) => f.debug_tuple("Fd").field(fd).finish(),
+            KeySource::Stdin => f.write_str("Stdin"),
+        }
+    }
+}
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SourceError::UnknownScheme(s) => write!(f, "unknown source '{}:', expected b64:, hex:, file:, env:, fd: or stdin:", s),
+            SourceError::InvalidFd(s) => write!(f, "'{}' is not a file descriptor number", s),
+            SourceError::StdioFd(fd) => write!(f, "file descriptor {} is standard input, output or error, use stdin: to read standard input", fd),
+            SourceError::Decode(e) => e.fmt(f),
+            SourceError::EnvNotSet(var) => write!(f, "environment variable {} is not set", var),
+            SourceError::EnvNotUnicode(var) => write!(f, "environment variable {} is not valid unicode", var),
+            SourceError::WrongLength(n) => write!(f, "expected 32 bytes, got {}", n),
+            SourceError::TooLong => f.write_str("expected 32 bytes, got more"),
+            SourceError::Io(what, e) => write!(f, "could not read {}: {}", what, e),
+        }
+    }
+}
+
+impl Error for SourceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SourceError::Decode(e) => Some(e),
+            SourceError::Io(_, e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<DecodeError> for SourceError {
+    fn from(e: DecodeError) -> Self {
+        SourceError::Decode(e)
+    }
+}
+
+impl KeySource {
+    pub fn parse(s: &str) -> Result<KeySource, SourceError> {
+        let (scheme, value) = match s.split_once(':') {
+            Some(parts) => parts,
+            // No encoding uses ':', so a bare value is unambiguous.
+            None => return Ok(KeySource::Bare(Zeroizing::new(s.to_string()))),
+        };
+
+        match scheme {
+            "b64" | "base64" => Ok(KeySource::Encoded(Encoding::Base64, Zeroizing::new(value.to_string()))),
+            "hex" => Ok(KeySource::Encoded(Encoding::Hex, Zeroizing::new(value.to_string()))),
+            "file" => Ok(KeySource::File(value.into())),
+            "env" => Ok(KeySource::Env(value.to_string())),
+            "fd" => match value.parse() {
+                Ok(fd @ 0..=2) => Err(SourceError::StdioFd(fd)),
+                Ok(fd) => Ok(KeySource::Fd(fd)),
+                Err(_) => Err(SourceError::InvalidFd(value.to_string())),
+            },
+            "stdin" if value.is_empty() => Ok(KeySource::Stdin),
+            _ => Err(SourceError::UnknownScheme(scheme.to_string())),
+        }
+    }
+
+    /// Reads the value with each source's default encoding.
+    pub fn read(&self) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+        self.read_as(None)
+    }
+
+    /// Reads the value, decoding it with `encoding` unless the scheme names
+    /// one itself.
+    pub fn read_as(&self, encoding: Option<Encoding>) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+        let text = encoding.unwrap_or(Encoding::Base64);
+        let stream = encoding.unwrap_or(Encoding::Raw);
+
+        match self {
+            KeySource::Encoded(encoding, value) => exact(&encoding.decode(value.as_bytes())?),
+            KeySource::Bare(value) => exact(&text.decode(value.as_bytes())?),
+            KeySource::File(path) => {
+                let what = path.display().to_string();
+                let file = File::open(path).map_err(|e| SourceError::Io(what.clone(), e))?;
+                read_stream(file, what, stream)
+            }
+            KeySource::Env(var) => match env::var(var) {
+                Ok(value) => exact(&text.decode(Zeroizing::new(value).as_bytes())?),
+                Err(env::VarError::NotPresent) => Err(SourceError::EnvNotSet(var.clone())),
+                Err(env::VarError::NotUnicode(_)) => Err(SourceError::EnvNotUnicode(var.clone())),
+            },
+            KeySource::Fd(fd) => read_fd(*fd, stream),
+            KeySource::Stdin => read_stream(io::stdin().lock(), "standard input".to_string(), stream),
+        }
+    }
+}
+
+/// Parses and reads `source` in one go.
+pub fn resolve(source: &str) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+    KeySource::parse(source)?.read()
+}
+
+// Reads to EOF, stopping one byte past the longest valid value so a wrong
+// file is not slurped.
+fn read_stream<R: Read>(reader: R, what: String, encoding: Encoding) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+    let limit = if encoding.is_text() { MAX_ENCODED } else { 32 };
+    let mut buf = Zeroizing::new(Vec::new());
+    reader.take(limit + 1).read_to_end(&mut buf).map_err(|e| SourceError::Io(what, e))?;
+    if buf.len() as u64 > limit {
+        return Err(SourceError::TooLong);
+    }
+    exact(&encoding.decode(&buf)?)
+}
+
+#[cfg(unix)]
+fn read_fd(fd: i32, encoding: Encoding) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+    use std::mem::ManuallyDrop;
+    use std::os::unix::io::FromRawFd;
+
+    let what = format!("file descriptor {}", fd);
+    match fd {
+        0..=2 => return Err(SourceError::StdioFd(fd)),
+        fd if fd < 0 => return Err(SourceError::InvalidFd(fd.to_string())),
+        _ => {}
+    }
+    if unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(SourceError::Io(what, io::Error::last_os_error()));
+    }
+
+    // Borrowed, whoever opened the descriptor still owns and closes it.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    let regular = file.metadata().map_err(|e| SourceError::Io(what.clone(), e))?.is_file();
+    if regular {
+        read_stream(ReadAt { file: &file, pos: 0 }, what, encoding)
+    } else {
+        read_stream(&*file, what, encoding)
+    }
+}
+
+// Reads a file from the start without moving its offset.
+#[cfg(unix)]
+struct ReadAt<'a> {
+    file: &'a File,
+    pos: u64,
+}
+
+#[cfg(unix)]
+impl Read for ReadAt<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        use std::os::unix::fs::FileExt;
+
+        let n = self.file.read_at(buf, self.pos)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(not(unix))]
+fn read_fd(fd: i32, _encoding: Encoding) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+    Err(SourceError::Io(format!("file descriptor {}", fd), io::Error::new(io::ErrorKind::Unsupported, "not supported on this platform")))
+}
+
+fn exact(bytes: &[u8]) -> Result<Zeroizing<[u8; 32]>, SourceError> {
+    let mut value = Zeroizing::new([0; 32]);
+    if bytes.len() != value.len() {
+        return Err(SourceError::WrongLength(bytes.len()));
+    }
+    value.copy_from_slice(bytes);
+    Ok(value)
+}
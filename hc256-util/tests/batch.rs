@@ -0,0 +1,192 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use hc256_util::batch::{process_paths, Options, Outcome, Summary, HEADER_LEN, MAGIC};
+
+const KEY: [u8; 32] = [7; 32];
+const IV: [u8; 32] = [9; 32];
+
+// A fresh directory per test, tests run in parallel.
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("hc256-util-batch-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(roots: &[&Path], options: &Options) -> (Summary, Vec<(PathBuf, String)>) {
+    let mut skipped = Vec::new();
+    let summary = process_paths(roots, options, &KEY, &IV, |path, outcome| match outcome {
+        Outcome::Done(_) => {}
+        Outcome::Skipped(reason) => skipped.push((path.to_path_buf(), reason.to_string())),
+        Outcome::Failed(e) => panic!("{}: {}", path.display(), e),
+    });
+    (summary, skipped)
+}
+
+#[test]
+fn round_trip_next_to_inputs() {
+    let dir = scratch("round-trip");
+    fs::create_dir(dir.join("sub")).unwrap();
+    fs::write(dir.join("a.txt"), b"first file").unwrap();
+    fs::write(dir.join("sub/b.bin"), [0xaa; 5000]).unwrap();
+
+    let options = Options { recursive: true, ..Options::default() };
+    let (summary, _) = run(&[&dir], &options);
+    assert_eq!(summary, Summary { done: 2, skipped: 0, failed: 0, bytes: 5010 });
+
+    let sealed = fs::read(dir.join("sub/b.bin.hc256")).unwrap();
+    assert_eq!(sealed.len(), HEADER_LEN + 5000);
+    assert_eq!(sealed[..MAGIC.len()], MAGIC);
+
+    fs::remove_file(dir.join("a.txt")).unwrap();
+    fs::remove_file(dir.join("sub/b.bin")).unwrap();
+    let options = Options { decrypt: true, ..options };
+    let (summary, _) = run(&[&dir], &options);
+    // Plaintext bytes, not the header-carrying input lengths.
+    assert_eq!(summary, Summary { done: 2, skipped: 0, failed: 0, bytes: 5010 });
+    assert_eq!(fs::read(dir.join("a.txt")).unwrap(), b"first file");
+    assert_eq!(fs::read(dir.join("sub/b.bin")).unwrap(), [0xaa; 5000]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn suffix_is_added_and_removed() {
+    let dir = scratch("suffix");
+    fs::write(dir.join("plain"), b"x").unwrap();
+    fs::write(dir.join("done.enc"), b"y").unwrap();
+
+    let options = Options { recursive: true, suffix: ".enc".to_string(), ..Options::default() };
+    let (summary, skipped) = run(&[&dir], &options);
+    assert_eq!(summary.done, 1);
+    assert_eq!(skipped, [(dir.join("done.enc"), "already has the encrypted suffix".to_string())]);
+    assert!(dir.join("plain.enc").is_file());
+
+    fs::remove_file(dir.join("plain")).unwrap();
+    let options = Options { decrypt: true, ..options };
+    let (summary, _) = run(&[&dir.join("plain.enc")], &options);
+    assert_eq!(summary.done, 1);
+    assert_eq!(fs::read(dir.join("plain")).unwrap(), b"x");
+
+    let (_, skipped) = run(&[&dir.join("plain")], &options);
+    assert_eq!(skipped, [(dir.join("plain"), "no suffix to remove".to_string())]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mirror_keeps_layout() {
+    let dir = scratch("mirror");
+    let tree = dir.join("tree");
+    let mirror = dir.join("out");
+    fs::create_dir_all(tree.join("sub")).unwrap();
+    fs::write(tree.join("sub/file"), b"data").unwrap();
+    fs::write(dir.join("single"), b"one").unwrap();
+
+    let options = Options { recursive: true, mirror: Some(mirror.clone()), ..Options::default() };
+    let (summary, _) = run(&[&tree, &dir.join("single")], &options);
+    assert_eq!(summary.done, 2);
+    assert!(mirror.join("tree/sub/file.hc256").is_file());
+    assert!(mirror.join("single.hc256").is_file());
+
+    let plain = dir.join("plain");
+    let options = Options { decrypt: true, mirror: Some(plain.clone()), ..options };
+    let (summary, _) = run(&[&mirror.join("tree")], &options);
+    assert_eq!(summary.done, 1);
+    assert_eq!(fs::read(plain.join("tree/sub/file")).unwrap(), b"data");
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mirror_of_parent_stays_inside() {
+    let dir = scratch("mirror-parent");
+    let inner = dir.join("inner");
+    fs::create_dir(&inner).unwrap();
+    fs::write(dir.join("file"), b"data").unwrap();
+
+    let mirror = dir.join("out");
+    let options = Options { recursive: true, mirror: Some(mirror.clone()), ..Options::default() };
+    let (summary, _) = run(&[&inner.join("..")], &options);
+    assert_eq!(summary.done, 1);
+
+    let name = dir.file_name().unwrap();
+    assert!(mirror.join(name).join("file.hc256").is_file());
+    assert!(!dir.join("file.hc256").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn skips_links_and_special_files() {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let dir = scratch("special");
+    fs::write(dir.join("real"), b"data").unwrap();
+    std::os::unix::fs::symlink(dir.join("real"), dir.join("link")).unwrap();
+    let fifo = CString::new(dir.join("fifo").as_os_str().as_bytes()).unwrap();
+    assert_eq!(unsafe { libc::mkfifo(fifo.as_ptr(), 0o600) }, 0);
+
+    let options = Options { recursive: true, ..Options::default() };
+    let (summary, skipped) = run(&[&dir], &options);
+    assert_eq!(summary.done, 1);
+    assert_eq!(
+        skipped,
+        [(dir.join("fifo"), "not a regular file".to_string()), (dir.join("link"), "symbolic link".to_string())]
+    );
+    assert!(!dir.join("link.hc256").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn each_file_gets_its_own_nonce() {
+    let dir = scratch("nonce");
+    fs::write(dir.join("a"), [0; 64]).unwrap();
+    fs::write(dir.join("b"), [0; 64]).unwrap();
+
+    let options = Options { recursive: true, ..Options::default() };
+    run(&[&dir], &options);
+
+    let a = fs::read(dir.join("a.hc256")).unwrap();
+    let b = fs::read(dir.join("b.hc256")).unwrap();
+    assert_ne!(a[MAGIC.len()..HEADER_LEN], b[MAGIC.len()..HEADER_LEN]);
+    assert_ne!(a[HEADER_LEN..], b[HEADER_LEN..]);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failure_exits_nonzero_after_the_rest() {
+    let dir = scratch("failure");
+    fs::write(dir.join("bogus.hc256"), b"not a batch file at all, far too plain").unwrap();
+    fs::write(dir.join("good"), b"data").unwrap();
+
+    let key = "01".repeat(32);
+    let output = Command::new(env!("CARGO_BIN_EXE_hc256-util"))
+        .args(["batch", "-E", "hex", "-k", &key, "-i", &key])
+        .arg(dir.join("good"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    fs::remove_file(dir.join("good")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hc256-util"))
+        .args(["batch", "-d", "-r", "-E", "hex", "-k", &key, "-i", &key])
+        .arg(&dir)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{}", stderr);
+    assert!(stderr.contains("not a file written by hc256-util batch"), "{}", stderr);
+    assert!(stderr.contains("1 files decrypted (4 bytes), 0 skipped, 1 failed"), "{}", stderr);
+    assert_eq!(fs::read(dir.join("good")).unwrap(), b"data");
+    assert!(!dir.join("bogus").exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,36 @@
+use hc256_util::encoding::{DecodeError, Encoding};
+
+#[test]
+fn round_trips() {
+    let data: Vec<u8> = (0..=255).collect();
+
+    for name in Encoding::NAMES {
+        let encoding: Encoding = name.parse().unwrap();
+        assert_eq!(*encoding.decode(&encoding.encode(&data)).unwrap(), data, "{}", name);
+        for len in 0..5 {
+            assert_eq!(*encoding.decode(&encoding.encode(&data[..len])).unwrap(), data[..len], "{}", name);
+        }
+    }
+}
+
+#[test]
+fn base64_variants() {
+    let data = [0xfb, 0xff, 0xbf, 0x01];
+    assert_eq!(*Encoding::Base64.encode(&data), *b"+/+/AQ==");
+    assert_eq!(*Encoding::Base64NoPad.encode(&data), *b"+/+/AQ");
+    assert_eq!(*Encoding::Base64Url.encode(&data), *b"-_-_AQ==");
+    assert_eq!(*Encoding::Base64UrlNoPad.encode(&data), *b"-_-_AQ");
+
+    for text in ["+/+/AQ==", "+/+/AQ", "-_-_AQ==", "-_-_AQ\n"] {
+        assert_eq!(*Encoding::Base64.decode(text.as_bytes()).unwrap(), data);
+    }
+}
+
+#[test]
+fn errors() {
+    assert_eq!(*Encoding::Hex.encode(&[0xab, 0x01]), *b"ab01");
+    assert_eq!(Encoding::Hex.decode(b"ab0").unwrap_err(), DecodeError::InvalidHex(3));
+    assert_eq!(Encoding::Hex.decode(b"  abx1").unwrap_err(), DecodeError::InvalidHex(2));
+    assert!(Encoding::Base64.decode(b"a*b=").is_err());
+    assert!("base32".parse::<Encoding>().is_err());
+}
@@ -0,0 +1,146 @@
+use std::env;
+use std::fs;
+use std::io::{self, Cursor, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use hc256::BufHc256;
+use hc256_util::filter::{pipe, same_file, Failure, BROKEN_PIPE_STATUS};
+
+const KEY: &str = "hex:0101010101010101010101010101010101010101010101010101010101010101";
+const IV: &str = "hex:0202020202020202020202020202020202020202020202020202020202020202";
+
+fn temp(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("hc256-util-filter-{}-{}", name, std::process::id()))
+}
+
+fn util(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_hc256-util"));
+    command.args(["-k", KEY, "-i", IV]).args(args);
+    command
+}
+
+// Runs `args` with `input` on standard input and returns standard output.
+fn filter(args: &[&str], input: &[u8]) -> Vec<u8> {
+    let mut child = util(args).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input).unwrap());
+
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    assert!(output.status.success());
+    output.stdout
+}
+
+struct BrokenReader;
+
+impl Read for BrokenReader {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::ConnectionReset.into())
+    }
+}
+
+struct BrokenWriter;
+
+impl Write for BrokenWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn pipe_matches_apply_stream() {
+    let data: Vec<u8> = (0..200_001).map(|i| i as u8).collect();
+    let mut out = Vec::new();
+    let copied = pipe(&mut BufHc256::new(&[1; 32], &[2; 32]), Cursor::new(&data), &mut out).unwrap();
+
+    let mut expected = data.clone();
+    BufHc256::new(&[1; 32], &[2; 32]).apply_stream(&mut expected);
+    assert_eq!(copied, data.len() as u64);
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn pipe_reports_which_side_failed() {
+    let mut cipher = BufHc256::new(&[1; 32], &[2; 32]);
+    match pipe(&mut cipher, Cursor::new([0; 10]), BrokenWriter) {
+        Err(Failure::Write(e)) => assert_eq!(e.kind(), io::ErrorKind::BrokenPipe),
+        other => panic!("expected a write failure, got {:?}", other),
+    }
+
+    let unreadable = io::repeat(0).take(10).chain(BrokenReader);
+    assert!(matches!(pipe(&mut cipher, unreadable, io::sink()), Err(Failure::Read(_))));
+}
+
+#[test]
+fn stdin_to_stdout_round_trip() {
+    let data: Vec<u8> = (0..300_000).map(|i| (i * 7) as u8).collect();
+    let encrypted = filter(&["encrypt"], &data);
+    assert_ne!(encrypted, data);
+    assert_eq!(encrypted.len(), data.len());
+
+    assert_eq!(filter(&["decrypt"], &encrypted), data);
+    assert_eq!(filter(&["-f", "-"], &encrypted), data);
+}
+
+#[test]
+fn broken_pipe_exits_like_sigpipe() {
+    let mut child = util(&["decrypt"]).stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    drop(child.stdout.take());
+
+    // The child may exit before all of this is written.
+    let _ = child.stdin.take().unwrap().write_all(&vec![0; 1 << 20]);
+    assert_eq!(child.wait().unwrap().code(), Some(BROKEN_PIPE_STATUS));
+}
+
+#[test]
+fn refuses_to_overwrite_its_input() {
+    let path = temp("same");
+    fs::write(&path, b"keep me").unwrap();
+    let file = path.to_str().unwrap();
+
+    let output = util(&["encrypt", "-f", file, "-o", file]).output().unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(fs::read(&path).unwrap(), b"keep me");
+
+    let link = temp("same-link");
+    let _ = fs::remove_file(&link);
+    fs::hard_link(&path, &link).unwrap();
+    assert!(same_file(&path, &link));
+    assert!(!same_file(&path, &temp("missing")));
+
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&link).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn refuses_ciphertext_on_a_terminal() {
+    use std::ffi::CStr;
+    use std::fs::OpenOptions;
+
+    let (tty, keep) = unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0 && libc::grantpt(master) == 0 && libc::unlockpt(master) == 0);
+        let name = CStr::from_ptr(libc::ptsname(master)).to_str().unwrap().to_string();
+        (OpenOptions::new().write(true).open(name).unwrap(), master)
+    };
+
+    let output = util(&["encrypt"])
+        .stdin(Stdio::null())
+        .stdout(tty.try_clone().unwrap())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--force"));
+
+    let forced = util(&["encrypt", "--force"]).stdin(Stdio::null()).stdout(tty).status().unwrap();
+    assert!(forced.success());
+    unsafe { libc::close(keep) };
+}
@@ -0,0 +1,149 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use hc256::Hc256;
+use hc256_util::encoding::Encoding;
+
+fn temp(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("hc256-util-keygen-{}-{}", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn keygen(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hc256-util")).arg("keygen").args(args).output().unwrap()
+}
+
+fn path_str(path: &Path) -> &str {
+    path.to_str().unwrap()
+}
+
+#[cfg(unix)]
+fn mode(path: &Path) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn writes_raw_files() {
+    let key = temp("raw-key");
+    let iv = temp("raw-iv");
+
+    let output = keygen(&["--key-out", path_str(&key), "--iv-out", path_str(&iv)]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.is_empty());
+
+    let (key_bytes, iv_bytes) = (fs::read(&key).unwrap(), fs::read(&iv).unwrap());
+    assert_eq!(key_bytes.len(), 32);
+    assert_eq!(iv_bytes.len(), 32);
+    assert_ne!(key_bytes, iv_bytes);
+    #[cfg(unix)]
+    {
+        assert_eq!(mode(&key), 0o600);
+        assert_eq!(mode(&iv), 0o600);
+    }
+
+    fs::remove_file(&key).unwrap();
+    fs::remove_file(&iv).unwrap();
+}
+
+#[test]
+fn only_requested_values_are_written() {
+    let iv = temp("only-iv");
+
+    let output = keygen(&["--iv-out", path_str(&iv)]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(output.stdout.is_empty());
+    assert_eq!(fs::read(&iv).unwrap().len(), 32);
+
+    fs::remove_file(&iv).unwrap();
+}
+
+#[test]
+fn refuses_to_overwrite_without_force() {
+    let key = temp("existing");
+    fs::write(&key, b"keep me").unwrap();
+
+    let output = keygen(&["--key-out", path_str(&key)]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --force to overwrite"));
+    assert_eq!(fs::read(&key).unwrap(), b"keep me");
+
+    fs::remove_file(&key).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn force_overwrites_and_tightens_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let key = temp("force");
+    fs::write(&key, b"an old key that is longer than a new one").unwrap();
+    fs::set_permissions(&key, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let output = keygen(&["--key-out", path_str(&key), "--force"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(fs::read(&key).unwrap().len(), 32);
+    assert_eq!(mode(&key), 0o600);
+
+    fs::remove_file(&key).unwrap();
+}
+
+#[test]
+fn file_encodings() {
+    for (encoding, len) in [("raw", 32), ("hex", 65), ("base64", 45), ("base64url-nopad", 44)] {
+        let key = temp(encoding);
+
+        let output = keygen(&["--key-out", path_str(&key), "--encoding", encoding]);
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let contents = fs::read(&key).unwrap();
+        assert_eq!(contents.len(), len, "{}", encoding);
+        let decoded = encoding.parse::<Encoding>().unwrap().decode(&contents).unwrap();
+        assert_eq!(decoded.len(), 32, "{}", encoding);
+
+        fs::remove_file(&key).unwrap();
+    }
+}
+
+#[test]
+fn prints_key_and_iv_one_per_line() {
+    let output = keygen(&[]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_ne!(lines[0], lines[1]);
+    for line in lines {
+        assert_eq!(line.len(), 44);
+        assert_eq!(Encoding::Base64.decode(line.as_bytes()).unwrap().len(), 32);
+    }
+
+    let output = keygen(&["--encoding", "hex"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().all(|line| line.len() == 64));
+    assert_eq!(stdout.lines().count(), 2);
+}
+
+#[test]
+fn fingerprint_matches_written_key() {
+    let key = temp("fingerprint");
+
+    let output = keygen(&["--key-out", path_str(&key), "--fingerprint"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let value: [u8; 32] = fs::read(&key).unwrap().try_into().unwrap();
+    let mut check = [0; 8];
+    Hc256::new(&value, b"hc256-util fingerprint\0\0\0\0\0\0\0\0\0\0").apply_stream(&mut check);
+    let expected = format!(
+        "key fingerprint: {:02x}{:02x}:{:02x}{:02x}:{:02x}{:02x}:{:02x}{:02x}\n",
+        check[0], check[1], check[2], check[3], check[4], check[5], check[6], check[7]
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stderr), expected);
+
+    fs::remove_file(&key).unwrap();
+}
@@ -0,0 +1,42 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use hc256::Hc256;
+use hc256_util::encoding::Encoding;
+
+#[test]
+fn output_encoding_does_not_apply_to_key_files() {
+    let path = env::temp_dir().join(format!("hc256-util-keystream-key-{}", std::process::id()));
+    fs::write(&path, [0x55; 32]).unwrap();
+    let key = format!("file:{}", path.display());
+
+    let output = Command::new(env!("CARGO_BIN_EXE_hc256-util"))
+        .args(["keystream", "--encoding", "hex", "--key", &key, "--iv", &key])
+        .args(["-n", "40", "--offset", "9"])
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let mut expected = [0; 49];
+    Hc256::new(&[0x55; 32], &[0x55; 32]).apply_stream(&mut expected);
+    let mut hex = Encoding::Hex.encode(&expected[9..]).to_vec();
+    hex.push(b'\n');
+    assert_eq!(output.stdout, hex);
+}
+
+#[test]
+fn key_encoding_decodes_bare_values() {
+    let output = Command::new(env!("CARGO_BIN_EXE_hc256-util"))
+        .args(["keystream", "-E", "hex", "-k", &"01".repeat(32), "-i", &"02".repeat(32), "-n", "8"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let mut expected = [0; 8];
+    Hc256::new(&[1; 32], &[2; 32]).apply_stream(&mut expected);
+    let mut b64 = Encoding::Base64.encode(&expected).to_vec();
+    b64.push(b'\n');
+    assert_eq!(output.stdout, b64);
+}
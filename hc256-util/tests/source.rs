@@ -0,0 +1,104 @@
+use std::env;
+use std::fs;
+
+use hc256_util::encoding::{DecodeError, Encoding};
+use hc256_util::source::{resolve, KeySource, SourceError};
+
+const KEY_B64: &str = "AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8=";
+const KEY_HEX: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+
+fn key() -> [u8; 32] {
+    let mut key = [0; 32];
+    for (i, b) in key.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    key
+}
+
+#[test]
+fn encoded_sources() {
+    assert_eq!(*resolve(&format!("b64:{}", KEY_B64)).unwrap(), key());
+    assert_eq!(*resolve(KEY_B64).unwrap(), key());
+    assert_eq!(*resolve(&format!("hex:{}", KEY_HEX.to_uppercase())).unwrap(), key());
+
+    let url = Encoding::Base64UrlNoPad.encode(&[0xfb; 32]);
+    let url = std::str::from_utf8(&url).unwrap();
+    assert!(url.contains('-') && !url.ends_with('='));
+    assert_eq!(*resolve(&format!("b64:{}", url)).unwrap(), [0xfb; 32]);
+    assert_eq!(*resolve(url).unwrap(), [0xfb; 32]);
+}
+
+#[test]
+fn read_as_overrides_default_encoding() {
+    assert_eq!(*KeySource::parse(KEY_HEX).unwrap().read_as(Some(Encoding::Hex)).unwrap(), key());
+    // An explicit scheme wins over the override.
+    assert_eq!(*KeySource::parse(&format!("b64:{}", KEY_B64)).unwrap().read_as(Some(Encoding::Hex)).unwrap(), key());
+
+    let path = env::temp_dir().join(format!("hc256-util-source-hex-{}", std::process::id()));
+    fs::write(&path, format!("{}\n", KEY_HEX)).unwrap();
+    let source = KeySource::File(path.clone());
+    assert_eq!(*source.read_as(Some(Encoding::Hex)).unwrap(), key());
+    assert!(matches!(source.read(), Err(SourceError::TooLong)));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn file_and_env_sources() {
+    let path = env::temp_dir().join(format!("hc256-util-source-{}", std::process::id()));
+    fs::write(&path, key()).unwrap();
+    assert_eq!(*resolve(&format!("file:{}", path.display())).unwrap(), key());
+
+    fs::write(&path, [0; 33]).unwrap();
+    assert!(matches!(resolve(&format!("file:{}", path.display())), Err(SourceError::TooLong)));
+    fs::remove_file(&path).unwrap();
+    assert!(matches!(resolve(&format!("file:{}", path.display())), Err(SourceError::Io(..))));
+
+    env::set_var("HC256_UTIL_SOURCE_TEST", KEY_B64);
+    assert_eq!(*resolve("env:HC256_UTIL_SOURCE_TEST").unwrap(), key());
+    assert!(matches!(resolve("env:HC256_UTIL_SOURCE_UNSET"), Err(SourceError::EnvNotSet(_))));
+}
+
+#[test]
+fn parse_errors() {
+    assert!(matches!(KeySource::parse("ftp:x"), Err(SourceError::UnknownScheme(s)) if s == "ftp"));
+    assert!(matches!(KeySource::parse("fd:three"), Err(SourceError::InvalidFd(_))));
+    for stdio in ["fd:0", "fd:1", "fd:2"] {
+        assert!(matches!(KeySource::parse(stdio), Err(SourceError::StdioFd(_))));
+    }
+    assert!(matches!(KeySource::parse("stdin:extra"), Err(SourceError::UnknownScheme(_))));
+    assert_eq!(KeySource::parse("fd:3").unwrap(), KeySource::Fd(3));
+    assert_eq!(KeySource::parse("stdin:").unwrap(), KeySource::Stdin);
+}
+
+#[test]
+fn decode_errors() {
+    assert!(matches!(resolve("hex:00g0"), Err(SourceError::Decode(DecodeError::InvalidHex(2)))));
+    assert!(matches!(resolve("hex:000"), Err(SourceError::Decode(DecodeError::InvalidHex(3)))));
+    assert!(matches!(resolve("hex:0000"), Err(SourceError::WrongLength(2))));
+    assert!(matches!(resolve("b64:!!!!"), Err(SourceError::Decode(DecodeError::InvalidBase64(_)))));
+    assert_eq!(resolve("b64:AAAA").unwrap_err().to_string(), "expected 32 bytes, got 3");
+}
+
+#[cfg(unix)]
+#[test]
+fn same_fd_for_key_and_iv() {
+    use std::io::Read;
+    use std::os::unix::io::AsRawFd;
+
+    let path = env::temp_dir().join(format!("hc256-util-source-fd-{}", std::process::id()));
+    fs::write(&path, key()).unwrap();
+    let mut file = fs::File::open(&path).unwrap();
+    let fd = format!("fd:{}", file.as_raw_fd());
+
+    assert_eq!(*resolve(&fd).unwrap(), key());
+    assert_eq!(*resolve(&fd).unwrap(), key());
+
+    // Still open and still at the start, the reads only borrowed it.
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents).unwrap();
+    assert_eq!(contents, key());
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(KeySource::Fd(1).read(), Err(SourceError::StdioFd(1))));
+    assert!(matches!(KeySource::Fd(4000).read(), Err(SourceError::Io(_, _))));
+}
@@ -0,0 +1,76 @@
+use poly1305::universal_hash::{KeyInit, UniversalHash};
+use poly1305::{Block, Key, Poly1305, Tag};
+
+use super::*;
+
+pub(crate) const TAG_LEN: usize = 16;
+
+// Mixes two counters into the tail of a base IV so every (hi, lo) pair
+// yields a distinct IV under the same key.
+pub(crate) fn derive_iv(base: &[u8; 32], hi: u64, lo: u64) -> [u8; 32] {
+    let mut iv = *base;
+    let hi = hi.to_le_bytes();
+    let lo = lo.to_le_bytes();
+
+    for i in 0..8 {
+        iv[16 + i] ^= hi[i];
+        iv[24 + i] ^= lo[i];
+    }
+
+    iv
+}
+
+// The first 32 bytes of keystream become a one-time Poly1305 key, the rest
+// encrypts `data` in place.
+pub(crate) fn seal(k: &[u8; 32], iv: &[u8; 32], aad: &[u8], data: &mut [u8]) -> [u8; TAG_LEN] {
+    let mut cipher = Hc256::new(k, iv);
+    let mut otk: [u8; 32] = [0; 32];
+    cipher.apply_stream(&mut otk);
+    cipher.apply_stream(data);
+
+    let tag = mac(&otk, aad, data).finalize();
+    otk.zeroize();
+
+    tag.into()
+}
+
+// Verifies `tag` over the ciphertext and only decrypts `data` when it matches.
+pub(crate) fn open(k: &[u8; 32], iv: &[u8; 32], aad: &[u8], data: &mut [u8], tag: &[u8]) -> bool {
+    if tag.len() != TAG_LEN {
+        return false;
+    }
+
+    let mut cipher = Hc256::new(k, iv);
+    let mut otk: [u8; 32] = [0; 32];
+    cipher.apply_stream(&mut otk);
+
+    let valid = mac(&otk, aad, data).verify(Tag::from_slice(tag)).is_ok();
+    otk.zeroize();
+
+    if valid {
+        cipher.apply_stream(data);
+    }
+
+    valid
+}
+
+pub(crate) fn authenticate(otk: &[u8; 32], aad: &[u8], ct: &[u8]) -> [u8; TAG_LEN] {
+    mac(otk, aad, ct).finalize().into()
+}
+
+pub(crate) fn verify(otk: &[u8; 32], aad: &[u8], ct: &[u8], tag: &[u8]) -> bool {
+    tag.len() == TAG_LEN && mac(otk, aad, ct).verify(Tag::from_slice(tag)).is_ok()
+}
+
+fn mac(otk: &[u8; 32], aad: &[u8], ct: &[u8]) -> Poly1305 {
+    let mut mac = Poly1305::new(Key::from_slice(otk));
+    mac.update_padded(aad);
+    mac.update_padded(ct);
+
+    let mut lengths: [u8; 16] = [0; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64).to_le_bytes());
+    lengths[8..].copy_from_slice(&(ct.len() as u64).to_le_bytes());
+    mac.update(&[Block::clone_from_slice(&lengths)]);
+
+    mac
+}
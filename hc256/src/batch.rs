@@ -0,0 +1,80 @@
+use super::*;
+
+/// Advances `N` independent HC-256 states in lockstep.
+///
+/// Every lane produces exactly the keystream a separate [`Hc256`] with the
+/// same key and IV would, generating one word per lane per step lets the
+/// lanes' table lookups overlap instead of waiting on each other.
+pub struct Hc256xN<const N: usize> {
+    lanes: Vec<Hc256>,
+}
+
+pub type Hc256x4 = Hc256xN<4>;
+
+pub type Hc256x8 = Hc256xN<8>;
+
+impl<const N: usize> Hc256xN<N> {
+    pub fn new(keys: &[[u8; 32]; N], ivs: &[[u8; 32]; N]) -> Self {
+        let mut lanes = Vec::with_capacity(N);
+        for (k, iv) in keys.iter().zip(ivs.iter()) {
+            lanes.push(Hc256::new(k, iv));
+        }
+        Hc256xN { lanes }
+    }
+
+    /// Encrypts `bufs[i]` under `keys[i]` and `ivs[i]` in one call.
+    pub fn encrypt(keys: &[[u8; 32]; N], ivs: &[[u8; 32]; N], bufs: &mut [&mut [u8]; N]) {
+        Hc256xN::new(keys, ivs).apply_streams(bufs);
+    }
+
+    /// Applies each lane's keystream to its buffer, with the same semantics
+    /// as [`Hc256::apply_stream`] per lane. Buffers may differ in length.
+    ///
+    /// All lanes step together while every buffer has whole words left,
+    /// then the lanes with longer buffers keep stepping together. Only the
+    /// last partial word of each buffer is handled one lane at a time.
+    pub fn apply_streams(&mut self, bufs: &mut [&mut [u8]; N]) {
+        let words: [usize; N] = std::array::from_fn(|i| bufs[i].len() / 4);
+        let shortest = words.iter().copied().min().unwrap_or(0);
+        let longest = words.iter().copied().max().unwrap_or(0);
+
+        let mut ks: [u32; N] = [0; N];
+        for w in 0..shortest {
+            for (word, lane) in ks.iter_mut().zip(self.lanes.iter_mut()) {
+                *word = lane.gen_word();
+            }
+
+            for (buf, word) in bufs.iter_mut().zip(ks.iter()) {
+                xor_word(buf, w, *word);
+            }
+        }
+        for w in shortest..longest {
+            for ((word, lane), &n) in ks.iter_mut().zip(self.lanes.iter_mut()).zip(words.iter()) {
+                if w < n {
+                    *word = lane.gen_word();
+                }
+            }
+
+            for ((buf, word), &n) in bufs.iter_mut().zip(ks.iter()).zip(words.iter()) {
+                if w < n {
+                    xor_word(buf, w, *word);
+                }
+            }
+        }
+        ks.zeroize();
+
+        for ((buf, lane), &n) in bufs.iter_mut().zip(self.lanes.iter_mut()).zip(words.iter()) {
+            lane.apply_stream(&mut buf[n * 4..]);
+        }
+    }
+}
+
+#[inline(always)]
+fn xor_word(buf: &mut [u8], w: usize, word: u32) {
+    let word = word.to_le_bytes();
+    let o = w * 4;
+    buf[o] ^= word[0];
+    buf[o + 1] ^= word[1];
+    buf[o + 2] ^= word[2];
+    buf[o + 3] ^= word[3];
+}
@@ -1,41 +1,6 @@
-use super::*;
-
-macro_rules! set_cipher_tables {
-    ($var:ident, $key:ident, $iv:ident) => {
-        let mut w: [u32; 2560] = [0; 2560];
-
-        for i in 0..8 {
-            w[i] = ($key[4 * i] as u32)
-                | (($key[(4 * i) + 1] as u32) << 8)
-                | (($key[(4 * i) + 2] as u32) << 16)
-                | (($key[(4 * i) + 3] as u32) << 24);
-
-            w[i + 8] = ($iv[4 * i] as u32)
-                | (($iv[(4 * i) + 1] as u32) << 8)
-                | (($iv[(4 * i) + 2] as u32) << 16)
-                | (($iv[(4 * i) + 3] as u32) << 24);
-        }
-
-        for i in 16..2560 {
-            w[i] = f2(w[i - 2])
-                .wrapping_add(w[i - 7])
-                .wrapping_add(f1(w[i - 15]))
-                .wrapping_add(w[i - 16])
-                .wrapping_add(i as u32);
-        }
-
-        $var.p[..1024].clone_from_slice(&w[512..(1024 + 512)]);
-        $var.q[..1024].clone_from_slice(&w[1536..(1024 + 1536)]);
+use std::fmt;
 
-        w.zeroize();
-
-        for _ in 0..4096 {
-            $var.gen_word();
-        }
-
-        $var.i.zeroize();
-    };
-}
+use super::*;
 
 #[derive(Zeroize)]
 #[zeroize(drop)]
@@ -45,17 +10,60 @@ pub struct BufHc256 {
     i: usize,
     r: [u8; 3],
     c: usize,
+    // Words generated since keying, `c` of their bytes are still unused.
+    pos: u64,
 }
 
 impl BufHc256 {
+    /// # Panics
+    ///
+    /// With the `self-test` feature, panics if the power-on self test fails.
+    /// Call [`self_test`] first to handle that as an error instead.
     pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> Self {
-        let mut cipher = BufHc256 { p: [0; 1024], q: [0; 1024], i: 0, r: [0; 3], c: 0 };
-        set_cipher_tables!(cipher, k, iv);
+        selftest::power_on();
+        BufHc256::new_unchecked(k, iv)
+    }
+
+    // `new` without the power-on self test, which itself uses it.
+    pub(crate) fn new_unchecked(k: &[u8; 32], iv: &[u8; 32]) -> Self {
+        let mut cipher = BufHc256 { p: [0; 1024], q: [0; 1024], i: 0, r: [0; 3], c: 0, pos: 0 };
+        setup::key_setup(&mut cipher.p, &mut cipher.q, k, iv);
         cipher
     }
 
+    /// Like `new`, but builds the state in place on the heap instead of
+    /// returning it by value.
+    pub fn new_boxed(k: &[u8; 32], iv: &[u8; 32]) -> Box<Self> {
+        selftest::power_on();
+        let mut cipher = BufHc256::zeroed_boxed();
+        setup::key_setup(&mut cipher.p, &mut cipher.q, k, iv);
+        cipher
+    }
+
+    /// Like `new_boxed`, but in locked memory that is kept out of swap and
+    /// core dumps.
+    #[cfg(all(feature = "secure-memory", target_os = "linux"))]
+    pub fn new_secure(k: &[u8; 32], iv: &[u8; 32]) -> std::io::Result<SecureBox<Self>> {
+        selftest::power_on();
+        // All-zero is a valid BufHc256.
+        let mut cipher: SecureBox<Self> = unsafe { SecureBox::zeroed()? };
+        let state = &mut *cipher;
+        setup::key_setup(&mut state.p, &mut state.q, k, iv);
+        Ok(cipher)
+    }
+
+    // All-zero is a valid BufHc256, just not a keyed one.
+    pub(crate) fn zeroed_boxed() -> Box<Self> {
+        unsafe { Box::new_zeroed().assume_init() }
+    }
+
     pub fn set_state(&mut self, k: &[u8; 32], iv: &[u8; 32], offset: usize) {
-        set_cipher_tables!(self, k, iv);
+        setup::key_setup(&mut self.p, &mut self.q, k, iv);
+        self.i = 0;
+        self.r.zeroize();
+        self.c = 0;
+        self.pos = 0;
+
         for _ in 0..(offset / 4) {
             self.gen_word();
         }
@@ -154,12 +162,73 @@ impl BufHc256 {
         pad.zeroize();
     }
 
+    /// Copies the full cipher state, both copies continue with the same
+    /// keystream.
+    ///
+    /// Encrypting different data with both copies reuses keystream and
+    /// exposes the XOR of the plaintexts. Only meant for trying several
+    /// decryptions of the same ciphertext. Each copy zeroizes on drop.
+    #[cfg(feature = "fork")]
+    pub fn fork_unchecked(&self) -> Self {
+        BufHc256 { p: self.p, q: self.q, i: self.i, r: self.r, c: self.c, pos: self.pos }
+    }
+
+    /// XORs keystream words into `dest`. Same result as `apply_stream` over
+    /// the words' little-endian bytes, and as cheap as `Hc256`'s version
+    /// when no bytes are buffered from an unaligned call.
+    pub fn apply_stream_words(&mut self, dest: &mut [u32]) {
+        if self.c == 0 {
+            for word in dest.iter_mut() {
+                *word ^= self.gen_word();
+            }
+        } else {
+            for word in dest.iter_mut() {
+                *word ^= self.next_u32();
+            }
+        }
+    }
+
+    /// Overwrites `dest` with keystream, consuming it like `apply_stream`.
+    pub fn fill_keystream(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+        self.apply_stream(dest);
+    }
+
+    /// The next 4 keystream bytes read little-endian, buffered bytes first.
+    pub fn next_u32(&mut self) -> u32 {
+        if self.c == 0 {
+            return self.gen_word();
+        }
+
+        let mut word: [u8; 4] = [0; 4];
+        self.apply_stream(&mut word);
+        let v = u32::from_le_bytes(word);
+        word.zeroize();
+        v
+    }
+
+    pub fn keystream_bytes(&mut self) -> KeystreamBytes<'_, Self> {
+        KeystreamBytes::new(self)
+    }
+
+    pub fn keystream_words(&mut self) -> KeystreamWords<'_, Self> {
+        KeystreamWords::new(self)
+    }
+
+    /// The `p` and `q` tables, for comparing against other implementations
+    /// when debugging test vectors.
+    #[cfg(feature = "debug-tables")]
+    pub fn debug_tables(&self) -> (&[u32; 1024], &[u32; 1024]) {
+        (&self.p, &self.q)
+    }
+
     #[inline]
     fn gen_word(&mut self) -> u32 {
         let i = self.i;
         let (j, j3, j10, j12, j1023) = self.offsets();
 
         self.i = (self.i + 1) & (2048 - 1);
+        self.pos += 1;
 
         if i < 1024 {
             self.p[j] = self.p[j]
@@ -217,4 +286,55 @@ impl BufHc256 {
             self.i.wrapping_add(1) & 1023
         )
     }
-}
\ No newline at end of file
+}
+
+// Never shows the tables or buffered bytes, only how many there are.
+// `position` counts keystream bytes used since keying, `table_index` wraps
+// every 2048 words like `Hc256`'s.
+impl fmt::Debug for BufHc256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufHc256")
+            .field("algorithm", &"HC-256")
+            .field("variant", &"byte-buffered")
+            .field("position", &(4 * self.pos).saturating_sub(self.c as u64))
+            .field("table_index", &self.i)
+            .field("buffered", &self.c)
+            .finish()
+    }
+}
+
+impl keystream::sealed::Source for BufHc256 {
+    fn next_u32(&mut self) -> u32 {
+        BufHc256::next_u32(self)
+    }
+
+    fn take_pending(&mut self) -> ([u8; 4], usize) {
+        let word = [0, self.r[0], self.r[1], self.r[2]];
+        let pos = 4 - self.c;
+        self.r.zeroize();
+        self.c = 0;
+        (word, pos)
+    }
+
+    fn restore(&mut self, rest: &[u8]) {
+        self.r = [0; 3];
+        self.r[3 - rest.len()..].copy_from_slice(rest);
+        self.c = rest.len();
+    }
+}
+
+#[cfg(feature = "serde")]
+impl BufHc256 {
+    pub(crate) fn to_raw(&self) -> material::RawState {
+        material::RawState { p: self.p.to_vec(), q: self.q.to_vec(), i: self.i, r: self.r, c: self.c, pos: self.pos }
+    }
+
+    // `raw` has passed `RawState::check`.
+    pub(crate) fn from_raw(raw: &material::RawState) -> Self {
+        selftest::power_on();
+        let mut cipher = BufHc256 { p: [0; 1024], q: [0; 1024], i: raw.i, r: raw.r, c: raw.c, pos: raw.pos };
+        cipher.p.copy_from_slice(&raw.p);
+        cipher.q.copy_from_slice(&raw.q);
+        cipher
+    }
+}
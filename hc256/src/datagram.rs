@@ -0,0 +1,153 @@
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use super::*;
+use crate::session::{Opener, Sealer, SessionError};
+
+pub use crate::session::OVERHEAD;
+
+pub const SESSION_ID_LEN: usize = 16;
+
+/// Largest UDP payload over IPv4.
+pub const MAX_DATAGRAM: usize = 65507;
+
+/// Largest plaintext that fits in a single datagram.
+pub const MAX_PAYLOAD: usize = MAX_DATAGRAM - OVERHEAD;
+
+/// Returns a fresh random session id from the OS RNG.
+pub fn new_session_id() -> io::Result<[u8; SESSION_ID_LEN]> {
+    let mut id: [u8; SESSION_ID_LEN] = [0; SESSION_ID_LEN];
+    getrandom::getrandom(&mut id)?;
+    Ok(id)
+}
+
+/// Seals packets so each can be opened on its own, in any order.
+///
+/// Packet IVs come from a salt bound to both the key and the session id, plus
+/// the packet number. A session id must never be reused with the same key.
+pub struct PacketSealer(Sealer);
+
+impl PacketSealer {
+    pub fn new(k: &[u8; 32], session_id: &[u8; SESSION_ID_LEN]) -> Self {
+        let mut salt = session_salt(k, session_id);
+        let sealer = Sealer::new(k, &salt);
+        salt.zeroize();
+        PacketSealer(sealer)
+    }
+
+    /// Number the next sealed packet will carry.
+    pub fn packet_number(&self) -> u64 {
+        self.0.sequence()
+    }
+
+    pub fn seal(&mut self, payload: &[u8]) -> Result<Vec<u8>, SessionError> {
+        self.0.seal(payload)
+    }
+}
+
+/// Opens packets from a [`PacketSealer`] with the same key and session id,
+/// dropping replays and packets that fell behind the replay window.
+pub struct PacketOpener(Opener);
+
+impl PacketOpener {
+    pub fn new(k: &[u8; 32], session_id: &[u8; SESSION_ID_LEN]) -> Self {
+        let mut salt = session_salt(k, session_id);
+        let opener = Opener::new(k, &salt);
+        salt.zeroize();
+        PacketOpener(opener)
+    }
+
+    pub fn open(&mut self, packet: &[u8]) -> Result<Vec<u8>, SessionError> {
+        self.0.open(packet)
+    }
+}
+
+/// A `UdpSocket` that seals outgoing and opens incoming datagrams.
+///
+/// Each direction has its own session id, the peer swaps the two.
+pub struct SecureUdpSocket {
+    socket: UdpSocket,
+    sealer: PacketSealer,
+    opener: PacketOpener,
+}
+
+impl SecureUdpSocket {
+    pub fn new(
+        socket: UdpSocket,
+        k: &[u8; 32],
+        send_session: &[u8; SESSION_ID_LEN],
+        recv_session: &[u8; SESSION_ID_LEN],
+    ) -> Self {
+        SecureUdpSocket {
+            socket,
+            sealer: PacketSealer::new(k, send_session),
+            opener: PacketOpener::new(k, recv_session),
+        }
+    }
+
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    pub fn into_inner(self) -> UdpSocket {
+        self.socket
+    }
+
+    /// Sends to the connected peer, returns the payload length.
+    pub fn send(&mut self, payload: &[u8]) -> io::Result<usize> {
+        let packet = self.seal(payload)?;
+        self.socket.send(&packet)?;
+        Ok(payload.len())
+    }
+
+    pub fn send_to<A: ToSocketAddrs>(&mut self, payload: &[u8], addr: A) -> io::Result<usize> {
+        let packet = self.seal(payload)?;
+        self.socket.send_to(&packet, addr)?;
+        Ok(payload.len())
+    }
+
+    /// Receives one datagram from the connected peer.
+    ///
+    /// Packets that fail to open are reported as `InvalidData` errors wrapping
+    /// the [`SessionError`], the socket stays usable afterwards. Payloads longer
+    /// than `buf` are truncated.
+    pub fn recv(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut packet = vec![0; MAX_DATAGRAM];
+        let len = self.socket.recv(&mut packet)?;
+        self.open(&packet[..len], buf)
+    }
+
+    pub fn recv_from(&mut self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let mut packet = vec![0; MAX_DATAGRAM];
+        let (len, addr) = self.socket.recv_from(&mut packet)?;
+        Ok((self.open(&packet[..len], buf)?, addr))
+    }
+
+    fn seal(&mut self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        if payload.len() > MAX_PAYLOAD {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "payload does not fit in a datagram"));
+        }
+
+        self.sealer.seal(payload).map_err(io::Error::other)
+    }
+
+    fn open(&mut self, packet: &[u8], buf: &mut [u8]) -> io::Result<usize> {
+        let mut payload = self.opener.open(packet).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+
+        let n = buf.len().min(payload.len());
+        buf[..n].copy_from_slice(&payload[..n]);
+        payload.zeroize();
+
+        Ok(n)
+    }
+}
+
+fn session_salt(k: &[u8; 32], session_id: &[u8; SESSION_ID_LEN]) -> [u8; 32] {
+    let mut iv: [u8; 32] = [0; 32];
+    iv[..SESSION_ID_LEN].copy_from_slice(session_id);
+    iv[SESSION_ID_LEN..].copy_from_slice(b"hc256 datagram\0\0");
+
+    let mut salt: [u8; 32] = [0; 32];
+    Hc256::new(k, &iv).apply_stream(&mut salt);
+    salt
+}
@@ -0,0 +1,85 @@
+use super::*;
+
+// Lets the iterators drive either cipher without exposing their internals.
+pub(crate) mod sealed {
+    pub trait Source {
+        fn next_u32(&mut self) -> u32;
+
+        // Hands over keystream bytes the cipher has buffered, as a word and
+        // the index of its first unused byte.
+        fn take_pending(&mut self) -> ([u8; 4], usize);
+
+        // Gives back unused bytes of the current word, fewer than 4.
+        fn restore(&mut self, rest: &[u8]);
+    }
+}
+
+/// Endless iterator over keystream bytes, from `keystream_bytes`.
+///
+/// Bytes left in the current word when it is dropped are zeroized. A
+/// `BufHc256` gets them back so its stream carries on where the iterator
+/// stopped, an `Hc256` discards them like a short `apply_stream` would.
+pub struct KeystreamBytes<'a, C: sealed::Source> {
+    cipher: &'a mut C,
+    word: [u8; 4],
+    pos: usize,
+}
+
+/// Endless iterator over keystream words, from `keystream_words`.
+///
+/// Words are the little-endian reading of the next 4 keystream bytes.
+pub struct KeystreamWords<'a, C: sealed::Source> {
+    cipher: &'a mut C,
+}
+
+impl<'a, C: sealed::Source> KeystreamBytes<'a, C> {
+    pub(crate) fn new(cipher: &'a mut C) -> Self {
+        let (word, pos) = cipher.take_pending();
+        KeystreamBytes { cipher, word, pos }
+    }
+}
+
+impl<C: sealed::Source> Iterator for KeystreamBytes<'_, C> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.pos == 4 {
+            self.word = self.cipher.next_u32().to_le_bytes();
+            self.pos = 0;
+        }
+
+        let byte = self.word[self.pos];
+        self.word[self.pos] = 0;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+impl<C: sealed::Source> Drop for KeystreamBytes<'_, C> {
+    fn drop(&mut self) {
+        self.cipher.restore(&self.word[self.pos..]);
+        self.word.zeroize();
+    }
+}
+
+impl<'a, C: sealed::Source> KeystreamWords<'a, C> {
+    pub(crate) fn new(cipher: &'a mut C) -> Self {
+        KeystreamWords { cipher }
+    }
+}
+
+impl<C: sealed::Source> Iterator for KeystreamWords<'_, C> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        Some(self.cipher.next_u32())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
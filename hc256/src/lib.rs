@@ -2,6 +2,7 @@ pub(crate) use std::ops::Drop;
 
 pub(crate) use zeroize::Zeroize;
 
+#[allow(clippy::upper_case_acronyms)]
 pub(crate) type TABLE = [u32; 1024];
 
 pub use buf::*;
@@ -10,6 +11,50 @@ mod buf;
 pub use reg::*;
 mod reg;
 
+pub use batch::*;
+mod batch;
+
+pub use prefetch::*;
+mod prefetch;
+
+pub use keystream::{KeystreamBytes, KeystreamWords};
+mod keystream;
+
+pub use pool::*;
+mod pool;
+
+pub use selftest::*;
+mod selftest;
+
+#[cfg(all(feature = "secure-memory", target_os = "linux"))]
+pub use secure::*;
+#[cfg(all(feature = "secure-memory", target_os = "linux"))]
+mod secure;
+
+mod aead;
+
+mod setup;
+
+mod threads;
+
+pub mod datagram;
+
+#[cfg(feature = "serde")]
+pub mod material;
+
+pub mod parallel;
+
+#[cfg(feature = "reference")]
+pub mod reference;
+
+pub mod sector;
+
+pub mod seekable;
+
+pub mod session;
+
+pub mod stream;
+
 #[inline]
 pub(crate) fn f1(x: u32) -> u32 {
     x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
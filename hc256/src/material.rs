@@ -0,0 +1,210 @@
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+use super::*;
+
+macro_rules! material {
+    ($name:ident, $what:literal) => {
+        #[doc = concat!("A 32-byte ", $what, ", zeroized on drop.")]
+        ///
+        /// Serializes as standard base64, or as hex through [`hex`] with
+        /// `#[serde(with = "hc256::material::hex")]`. Deserializing accepts
+        /// either. Equality is constant-time.
+        #[derive(Clone, Zeroize)]
+        #[zeroize(drop)]
+        pub struct $name(pub [u8; 32]);
+
+        impl $name {
+            pub fn as_bytes(&self) -> &[u8; 32] {
+                &self.0
+            }
+        }
+
+        impl ConstantTimeEq for $name {
+            fn ct_eq(&self, other: &Self) -> subtle::Choice {
+                self.0.ct_eq(&other.0)
+            }
+        }
+
+        impl PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                self.ct_eq(other).into()
+            }
+        }
+
+        impl Eq for $name {}
+
+        impl From<[u8; 32]> for $name {
+            fn from(bytes: [u8; 32]) -> Self {
+                $name(bytes)
+            }
+        }
+
+        impl AsRef<[u8; 32]> for $name {
+            fn as_ref(&self) -> &[u8; 32] {
+                &self.0
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(concat!(stringify!($name), "(..)"))
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let encoded = Zeroizing::new(base64::encode(self.0));
+                serializer.serialize_str(&encoded)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_str(MaterialVisitor).map($name)
+            }
+        }
+    };
+}
+
+material!(Key, "key");
+material!(Iv, "IV");
+
+/// Serde helpers that write a [`Key`] or [`Iv`] as lowercase hex.
+pub mod hex {
+    use super::*;
+
+    pub fn serialize<T: AsRef<[u8; 32]>, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoded = Zeroizing::new(String::with_capacity(64));
+        for byte in value.as_ref() {
+            encoded.push(char::from_digit((byte >> 4) as u32, 16).unwrap());
+            encoded.push(char::from_digit((byte & 0xf) as u32, 16).unwrap());
+        }
+        serializer.serialize_str(&encoded)
+    }
+
+    pub fn deserialize<'de, T: From<[u8; 32]>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        deserializer.deserialize_str(MaterialVisitor).map(T::from)
+    }
+}
+
+// Accepts 64 hex digits or base64 of 32 bytes.
+struct MaterialVisitor;
+
+impl<'de> Visitor<'de> for MaterialVisitor {
+    type Value = [u8; 32];
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("32 bytes as hex or base64")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<[u8; 32], E> {
+        let decoded = if v.len() == 64 && v.bytes().all(|b| b.is_ascii_hexdigit()) {
+            decode_hex(v)
+        } else {
+            Zeroizing::new(base64::decode(v).map_err(|_| E::custom("invalid base64"))?)
+        };
+
+        let mut bytes: [u8; 32] = [0; 32];
+        if decoded.len() != bytes.len() {
+            return Err(E::invalid_length(decoded.len(), &self));
+        }
+        bytes.copy_from_slice(&decoded);
+        Ok(bytes)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<[u8; 32], E> {
+        let v = Zeroizing::new(v);
+        self.visit_str(&v)
+    }
+}
+
+// Caller has checked for an even number of hex digits.
+fn decode_hex(v: &str) -> Zeroizing<Vec<u8>> {
+    let digit = |b: u8| (b as char).to_digit(16).unwrap() as u8;
+    Zeroizing::new(v.as_bytes().chunks(2).map(|pair| digit(pair[0]) << 4 | digit(pair[1])).collect())
+}
+
+/// Opt-in wrapper that lets a live `Hc256` or `BufHc256` be serialized.
+///
+/// The output contains the full internal tables, anyone holding it can
+/// produce the rest of the keystream. The ciphers themselves do not
+/// implement `Serialize`. Deserializing runs the power-on self test like
+/// `new` does.
+pub struct ExposedState<C>(pub C);
+
+// Intermediate form of a cipher's state, `r` and `c` are only used by
+// `BufHc256`.
+#[derive(Serialize, Deserialize, Zeroize)]
+#[zeroize(drop)]
+pub(crate) struct RawState {
+    pub(crate) p: Vec<u32>,
+    pub(crate) q: Vec<u32>,
+    pub(crate) i: usize,
+    #[serde(default)]
+    pub(crate) r: [u8; 3],
+    #[serde(default)]
+    pub(crate) c: usize,
+    #[serde(default)]
+    pub(crate) pos: u64,
+}
+
+impl RawState {
+    pub(crate) fn check(&self) -> Result<(), &'static str> {
+        if self.p.len() != 1024 || self.q.len() != 1024 {
+            return Err("tables must hold 1024 words");
+        }
+        if self.i >= 2048 || self.c > 3 {
+            return Err("counter out of range");
+        }
+        Ok(())
+    }
+}
+
+macro_rules! exposed_state {
+    ($cipher:ident) => {
+        impl Serialize for ExposedState<$cipher> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.to_raw().serialize(serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for ExposedState<$cipher> {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let raw = RawState::deserialize(deserializer)?;
+                raw.check().map_err(de::Error::custom)?;
+                Ok(ExposedState($cipher::from_raw(&raw)))
+            }
+        }
+    };
+}
+
+exposed_state!(Hc256);
+exposed_state!(BufHc256);
+
+// Zero sizes are rejected by the headers' constructors, reject them here too.
+pub(crate) fn nonzero<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de> + Default + PartialEq,
+    D: Deserializer<'de>,
+{
+    let value = T::deserialize(deserializer)?;
+    if value == T::default() {
+        return Err(de::Error::custom("size must not be zero"));
+    }
+    Ok(value)
+}
+
+// `parallel::Header::from_bytes` also bounds the segment size.
+pub(crate) fn segment_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value: u64 = nonzero(deserializer)?;
+    if value > crate::parallel::MAX_SEGMENT_SIZE {
+        return Err(de::Error::custom("segment size exceeds maximum"));
+    }
+    Ok(value)
+}
@@ -0,0 +1,104 @@
+use std::io;
+
+use super::*;
+use crate::aead::derive_iv;
+
+pub const MAGIC: [u8; 8] = *b"HC256PX\x02";
+
+pub const HEADER_LEN: usize = 16;
+
+pub const DEFAULT_SEGMENT_SIZE: u64 = 1 << 22;
+
+/// Largest segment size a header may carry, each thread buffers a segment.
+pub const MAX_SEGMENT_SIZE: u64 = 1 << 26;
+
+// Mixed into every segment IV so no segment runs under the master IV, or
+// under the IV another mode derives for the same index.
+const DOMAIN: u64 = u64::from_le_bytes(MAGIC);
+
+/// Records how a file was split so it can be decrypted in parallel too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::material::segment_size"))]
+    pub segment_size: u64,
+}
+
+impl Header {
+    pub fn new(segment_size: u64) -> Self {
+        assert!(segment_size > 0, "Segment size must not be zero");
+        assert!(segment_size <= MAX_SEGMENT_SIZE, "Segment size exceeds MAX_SEGMENT_SIZE");
+        Header { segment_size }
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes: [u8; HEADER_LEN] = [0; HEADER_LEN];
+        bytes[..8].copy_from_slice(&MAGIC);
+        bytes[8..].copy_from_slice(&self.segment_size.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; HEADER_LEN]) -> io::Result<Self> {
+        let segment_size = u64::from_le_bytes(bytes[8..].try_into().unwrap());
+        if bytes[..8] != MAGIC || segment_size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a parallel hc256 file"));
+        }
+        if segment_size > MAX_SEGMENT_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "segment size exceeds maximum"));
+        }
+
+        Ok(Header { segment_size })
+    }
+}
+
+/// Encrypts data as independent segments, each with its own `Hc256` keyed by
+/// an IV derived from the master IV and the segment index.
+///
+/// Encrypting and decrypting are the same operation.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct ParallelCipher {
+    k: [u8; 32],
+    iv: [u8; 32],
+    segment_size: u64,
+}
+
+impl ParallelCipher {
+    pub fn new(k: &[u8; 32], iv: &[u8; 32], header: Header) -> Self {
+        ParallelCipher { k: *k, iv: *iv, segment_size: header.segment_size }
+    }
+
+    pub fn header(&self) -> Header {
+        Header { segment_size: self.segment_size }
+    }
+
+    pub fn segment_iv(&self, index: u64) -> [u8; 32] {
+        derive_iv(&self.iv, DOMAIN, index)
+    }
+
+    /// Processes one segment, `data` may be shorter than a segment.
+    pub fn apply_segment(&self, index: u64, data: &mut [u8]) {
+        assert!(data.len() as u64 <= self.segment_size, "Data is larger than a segment");
+
+        let mut iv = self.segment_iv(index);
+        Hc256::new(&self.k, &iv).apply_stream(data);
+        iv.zeroize();
+    }
+
+    /// Processes consecutive segments starting at `first` on all available
+    /// cores.
+    pub fn apply(&self, first: u64, data: &mut [u8]) {
+        self.apply_threads(first, data, threads::available());
+    }
+
+    pub fn apply_threads(&self, first: u64, data: &mut [u8], threads: usize) {
+        threads::par_chunks_mut(data, self.segment_size as usize, threads, |i, segment| {
+            self.apply_segment(first + i as u64, segment);
+        });
+    }
+}
+
+/// Number of worker threads [`ParallelCipher::apply`] uses.
+pub fn available_threads() -> usize {
+    threads::available()
+}
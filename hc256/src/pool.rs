@@ -0,0 +1,102 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+
+use super::*;
+
+/// Fixed set of heap-allocated [`BufHc256`] states shared between threads.
+///
+/// All states are allocated up front, so checking one out only re-keys it
+/// and the pool never holds more than `capacity` of them. Cloning the pool
+/// is cheap and every clone hands out from the same set.
+#[derive(Clone)]
+pub struct CipherPool {
+    shared: Arc<Shared>,
+}
+
+/// Cipher state checked out of a [`CipherPool`], zeroized and returned to
+/// the pool when dropped.
+pub struct PooledCipher {
+    cipher: Option<Box<BufHc256>>,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    free: Mutex<Vec<Box<BufHc256>>>,
+    returned: Condvar,
+    capacity: usize,
+}
+
+impl CipherPool {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "Capacity must not be zero");
+        selftest::power_on();
+
+        let free = (0..capacity).map(|_| BufHc256::zeroed_boxed()).collect();
+        CipherPool { shared: Arc::new(Shared { free: Mutex::new(free), returned: Condvar::new(), capacity }) }
+    }
+
+    /// Checks out a state keyed with `k` and `iv`, waiting for one to be
+    /// returned if all are in use.
+    pub fn get(&self, k: &[u8; 32], iv: &[u8; 32]) -> PooledCipher {
+        let mut free = self.shared.lock();
+        let cipher = loop {
+            match free.pop() {
+                Some(cipher) => break cipher,
+                None => free = self.shared.returned.wait(free).unwrap(),
+            }
+        };
+        drop(free);
+
+        self.checkout(cipher, k, iv)
+    }
+
+    /// Like `get`, but returns `None` instead of waiting.
+    pub fn try_get(&self, k: &[u8; 32], iv: &[u8; 32]) -> Option<PooledCipher> {
+        let cipher = self.shared.lock().pop()?;
+        Some(self.checkout(cipher, k, iv))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.shared.capacity
+    }
+
+    /// Number of states not currently checked out.
+    pub fn available(&self) -> usize {
+        self.shared.lock().len()
+    }
+
+    fn checkout(&self, mut cipher: Box<BufHc256>, k: &[u8; 32], iv: &[u8; 32]) -> PooledCipher {
+        cipher.set_state(k, iv, 0);
+        PooledCipher { cipher: Some(cipher), shared: Arc::clone(&self.shared) }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Vec<Box<BufHc256>>> {
+        self.free.lock().unwrap()
+    }
+}
+
+impl Deref for PooledCipher {
+    type Target = BufHc256;
+
+    fn deref(&self) -> &BufHc256 {
+        self.cipher.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledCipher {
+    fn deref_mut(&mut self) -> &mut BufHc256 {
+        self.cipher.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledCipher {
+    fn drop(&mut self) {
+        if let Some(mut cipher) = self.cipher.take() {
+            cipher.zeroize();
+            self.shared.lock().push(cipher);
+            self.shared.returned.notify_one();
+        }
+    }
+}
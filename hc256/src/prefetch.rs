@@ -0,0 +1,176 @@
+use std::io;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+
+use super::*;
+
+pub const DEFAULT_PREFETCH_CAPACITY: usize = 65536;
+
+// Bytes the worker generates outside the lock before copying into the ring.
+const BLOCK: usize = 1024;
+
+/// Snapshot of a [`PrefetchingHc256`]'s ring buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrefetchMetrics {
+    /// Keystream bytes generated and waiting to be used.
+    pub depth: usize,
+    pub capacity: usize,
+    /// Times `apply_stream` found the buffer empty and had to wait.
+    pub stalls: u64,
+    pub consumed: u64,
+}
+
+/// Byte-granular HC-256 whose keystream is generated ahead of time on a
+/// dedicated thread.
+///
+/// Produces the same stream as a [`BufHc256`] with the same key and IV,
+/// `apply_stream` only XORs precomputed bytes and blocks when the buffer runs
+/// dry.
+pub struct PrefetchingHc256 {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    ring: Mutex<Ring>,
+    filled: Condvar,
+    drained: Condvar,
+}
+
+struct Ring {
+    buf: Vec<u8>,
+    head: usize,
+    len: usize,
+    stop: bool,
+    stalls: u64,
+    consumed: u64,
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        self.buf.zeroize();
+    }
+}
+
+impl PrefetchingHc256 {
+    /// Starts the keystream thread with [`DEFAULT_PREFETCH_CAPACITY`] bytes
+    /// of buffer.
+    ///
+    /// # Panics
+    ///
+    /// With the `self-test` feature, panics if the power-on self test fails.
+    pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> io::Result<Self> {
+        PrefetchingHc256::with_capacity(k, iv, DEFAULT_PREFETCH_CAPACITY)
+    }
+
+    /// Like `new`, with `capacity` bytes of buffer. Fails if `capacity` is
+    /// zero or the thread cannot be spawned.
+    ///
+    /// # Panics
+    ///
+    /// With the `self-test` feature, panics if the power-on self test fails.
+    pub fn with_capacity(k: &[u8; 32], iv: &[u8; 32], capacity: usize) -> io::Result<Self> {
+        if capacity == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "capacity must not be zero"));
+        }
+
+        let shared = Arc::new(Shared {
+            ring: Mutex::new(Ring { buf: vec![0; capacity], head: 0, len: 0, stop: false, stalls: 0, consumed: 0 }),
+            filled: Condvar::new(),
+            drained: Condvar::new(),
+        });
+
+        let cipher = Box::new(Hc256::new(k, iv));
+        let worker_shared = Arc::clone(&shared);
+        let worker = thread::Builder::new()
+            .name("hc256-prefetch".into())
+            .spawn(move || generate(cipher, &worker_shared))?;
+
+        Ok(PrefetchingHc256 { shared, worker: Some(worker) })
+    }
+
+    pub fn apply_stream(&mut self, dest: &mut [u8]) {
+        let mut done = 0;
+        let mut ring = self.shared.lock();
+
+        while done < dest.len() {
+            if ring.len == 0 {
+                ring.stalls += 1;
+                while ring.len == 0 {
+                    ring = self.shared.filled.wait(ring).unwrap();
+                }
+            }
+
+            let capacity = ring.buf.len();
+            let n = (dest.len() - done).min(ring.len).min(capacity - ring.head);
+            let head = ring.head;
+            for (d, k) in dest[done..done + n].iter_mut().zip(ring.buf[head..head + n].iter_mut()) {
+                *d ^= *k;
+                *k = 0;
+            }
+
+            ring.head = (head + n) % capacity;
+            ring.len -= n;
+            ring.consumed += n as u64;
+            done += n;
+            self.shared.drained.notify_one();
+        }
+    }
+
+    pub fn metrics(&self) -> PrefetchMetrics {
+        let ring = self.shared.lock();
+        PrefetchMetrics { depth: ring.len, capacity: ring.buf.len(), stalls: ring.stalls, consumed: ring.consumed }
+    }
+}
+
+impl Drop for PrefetchingHc256 {
+    fn drop(&mut self) {
+        self.shared.lock().stop = true;
+        self.shared.drained.notify_one();
+
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Ring> {
+        self.ring.lock().unwrap()
+    }
+}
+
+fn generate(mut cipher: Box<Hc256>, shared: &Shared) {
+    let mut block: [u8; BLOCK] = [0; BLOCK];
+    let mut pos = BLOCK;
+
+    loop {
+        if pos == BLOCK {
+            for word in block.chunks_exact_mut(4) {
+                word.copy_from_slice(&cipher.gen_word().to_le_bytes());
+            }
+            pos = 0;
+        }
+
+        let mut ring = shared.lock();
+        while ring.len == ring.buf.len() && !ring.stop {
+            ring = shared.drained.wait(ring).unwrap();
+        }
+        if ring.stop {
+            break;
+        }
+
+        let capacity = ring.buf.len();
+        let tail = (ring.head + ring.len) % capacity;
+        let n = (BLOCK - pos).min(capacity - ring.len).min(capacity - tail);
+        ring.buf[tail..tail + n].copy_from_slice(&block[pos..pos + n]);
+        block[pos..pos + n].zeroize();
+
+        ring.len += n;
+        pos += n;
+        shared.filled.notify_one();
+    }
+
+    block.zeroize();
+}
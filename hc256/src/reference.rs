@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt;
+
+/// HC-256 exactly as the specification's pseudocode describes it, for
+/// checking the optimised ciphers against. Slow on purpose.
+///
+/// Tables are indexed with explicit `mod 1024` arithmetic, the full
+/// expansion `W` is built during initialisation and keystream is produced
+/// one 32-bit word at a time.
+pub struct Hc256Ref {
+    p: Vec<u32>,
+    q: Vec<u32>,
+    i: u32,
+}
+
+impl Hc256Ref {
+    pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> Self {
+        let mut w = vec![0u32; 2560];
+        for i in 0..8 {
+            w[i] = u32::from_le_bytes([k[4 * i], k[4 * i + 1], k[4 * i + 2], k[4 * i + 3]]);
+            w[i + 8] = u32::from_le_bytes([iv[4 * i], iv[4 * i + 1], iv[4 * i + 2], iv[4 * i + 3]]);
+        }
+        for i in 16..2560 {
+            w[i] = f2(w[i - 2])
+                .wrapping_add(w[i - 7])
+                .wrapping_add(f1(w[i - 15]))
+                .wrapping_add(w[i - 16])
+                .wrapping_add(i as u32);
+        }
+
+        let mut cipher = Hc256Ref { p: w[512..1536].to_vec(), q: w[1536..2560].to_vec(), i: 0 };
+        for _ in 0..4096 {
+            cipher.next_word();
+        }
+        cipher
+    }
+
+    /// The next keystream word, its bytes go out little-endian.
+    pub fn next_word(&mut self) -> u32 {
+        let j = (self.i % 1024) as usize;
+        let s;
+
+        if self.i % 2048 < 1024 {
+            self.p[j] = self.p[j]
+                .wrapping_add(self.p[sub(j, 10)])
+                .wrapping_add(self.g1(self.p[sub(j, 3)], self.p[sub(j, 1023)]));
+            s = self.h1(self.p[sub(j, 12)]) ^ self.p[j];
+        } else {
+            self.q[j] = self.q[j]
+                .wrapping_add(self.q[sub(j, 10)])
+                .wrapping_add(self.g2(self.q[sub(j, 3)], self.q[sub(j, 1023)]));
+            s = self.h2(self.q[sub(j, 12)]) ^ self.q[j];
+        }
+
+        self.i = (self.i + 1) % 2048;
+        s
+    }
+
+    fn g1(&self, x: u32, y: u32) -> u32 {
+        (x.rotate_right(10) ^ y.rotate_right(23)).wrapping_add(self.q[((x ^ y) % 1024) as usize])
+    }
+
+    fn g2(&self, x: u32, y: u32) -> u32 {
+        (x.rotate_right(10) ^ y.rotate_right(23)).wrapping_add(self.p[((x ^ y) % 1024) as usize])
+    }
+
+    fn h1(&self, x: u32) -> u32 {
+        let [x0, x1, x2, x3] = x.to_le_bytes();
+        self.q[x0 as usize]
+            .wrapping_add(self.q[256 + x1 as usize])
+            .wrapping_add(self.q[512 + x2 as usize])
+            .wrapping_add(self.q[768 + x3 as usize])
+    }
+
+    fn h2(&self, x: u32) -> u32 {
+        let [x0, x1, x2, x3] = x.to_le_bytes();
+        self.p[x0 as usize]
+            .wrapping_add(self.p[256 + x1 as usize])
+            .wrapping_add(self.p[512 + x2 as usize])
+            .wrapping_add(self.p[768 + x3 as usize])
+    }
+}
+
+/// How a producer consumes keystream across calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// Unused bytes of a word carry over to the next call, as in `BufHc256`.
+    Byte,
+    /// Every call starts on a fresh word, as in `Hc256`.
+    Word,
+}
+
+/// First keystream byte where a producer disagreed with [`Hc256Ref`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Offset into the concatenated output of all calls.
+    pub offset: usize,
+    pub call: usize,
+    pub expected: u8,
+    pub actual: u8,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Keystream mismatch at byte {} (call {}): expected {:#04x}, got {:#04x}",
+            self.offset, self.call, self.expected, self.actual
+        )
+    }
+}
+
+impl Error for Mismatch {}
+
+/// Calls `apply` once per entry of `splits` with a zeroed buffer of that
+/// length and compares what it XORed in against `Hc256Ref` keyed the same.
+///
+/// `apply` should wrap a producer already keyed with `k` and `iv`, e.g.
+/// `|buf| cipher.apply_stream(buf)`.
+pub fn differential_check<F>(k: &[u8; 32], iv: &[u8; 32], splits: &[usize], granularity: Granularity, mut apply: F) -> Result<(), Mismatch>
+where
+    F: FnMut(&mut [u8]),
+{
+    let mut reference = Hc256Ref::new(k, iv);
+    let mut pending: Vec<u8> = Vec::new();
+    let mut offset = 0;
+
+    for (call, &len) in splits.iter().enumerate() {
+        if granularity == Granularity::Word {
+            pending.clear();
+        }
+        while pending.len() < len {
+            pending.extend_from_slice(&reference.next_word().to_le_bytes());
+        }
+        let expected: Vec<u8> = pending.drain(..len).collect();
+
+        let mut actual = vec![0; len];
+        apply(&mut actual);
+
+        if let Some(at) = expected.iter().zip(&actual).position(|(e, a)| e != a) {
+            return Err(Mismatch { offset: offset + at, call, expected: expected[at], actual: actual[at] });
+        }
+        offset += len;
+    }
+
+    Ok(())
+}
+
+fn f1(x: u32) -> u32 {
+    x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+}
+
+fn f2(x: u32) -> u32 {
+    x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+}
+
+// `x ⊟ y` from the specification, subtraction modulo 1024.
+fn sub(x: usize, y: usize) -> usize {
+    (x + 1024 - y) % 1024
+}
@@ -1,41 +1,6 @@
-use super::*;
-
-macro_rules! set_cipher_tables {
-    ($var:ident, $key:ident, $iv:ident) => {
-        let mut w: [u32; 2560] = [0; 2560];
-
-        for i in 0..8 {
-            w[i] = ($key[4 * i] as u32)
-                | (($key[(4 * i) + 1] as u32) << 8)
-                | (($key[(4 * i) + 2] as u32) << 16)
-                | (($key[(4 * i) + 3] as u32) << 24);
-
-            w[i + 8] = ($iv[4 * i] as u32)
-                | (($iv[(4 * i) + 1] as u32) << 8)
-                | (($iv[(4 * i) + 2] as u32) << 16)
-                | (($iv[(4 * i) + 3] as u32) << 24);
-        }
-
-        for i in 16..2560 {
-            w[i] = f2(w[i - 2])
-                .wrapping_add(w[i - 7])
-                .wrapping_add(f1(w[i - 15]))
-                .wrapping_add(w[i - 16])
-                .wrapping_add(i as u32);
-        }
+use std::fmt;
 
-        $var.p[..1024].clone_from_slice(&w[512..(1024 + 512)]);
-        $var.q[..1024].clone_from_slice(&w[1536..(1024 + 1536)]);
-
-        w.zeroize();
-
-        for _ in 0..4096 {
-            $var.gen_word();
-        }
-
-        $var.i.zeroize();
-    };
-}
+use super::*;
 
 #[derive(Zeroize)]
 #[zeroize(drop)]
@@ -43,17 +8,54 @@ pub struct Hc256 {
     p: TABLE,
     q: TABLE,
     i: usize,
+    // Words generated since keying.
+    pos: u64,
 }
 
 impl Hc256 {
+    /// # Panics
+    ///
+    /// With the `self-test` feature, panics if the power-on self test fails.
+    /// Call [`self_test`] first to handle that as an error instead.
     pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> Self {
-        let mut cipher = Hc256 { p: [0; 1024], q: [0; 1024], i: 0 };
-        set_cipher_tables!(cipher, k, iv);
+        selftest::power_on();
+        Hc256::new_unchecked(k, iv)
+    }
+
+    // `new` without the power-on self test, which itself uses it.
+    pub(crate) fn new_unchecked(k: &[u8; 32], iv: &[u8; 32]) -> Self {
+        let mut cipher = Hc256 { p: [0; 1024], q: [0; 1024], i: 0, pos: 0 };
+        setup::key_setup(&mut cipher.p, &mut cipher.q, k, iv);
+        cipher
+    }
+
+    /// Like `new`, but builds the state in place on the heap instead of
+    /// returning it by value.
+    pub fn new_boxed(k: &[u8; 32], iv: &[u8; 32]) -> Box<Self> {
+        selftest::power_on();
+        // All-zero is a valid Hc256.
+        let mut cipher: Box<Self> = unsafe { Box::new_zeroed().assume_init() };
+        setup::key_setup(&mut cipher.p, &mut cipher.q, k, iv);
         cipher
     }
 
+    /// Like `new_boxed`, but in locked memory that is kept out of swap and
+    /// core dumps.
+    #[cfg(all(feature = "secure-memory", target_os = "linux"))]
+    pub fn new_secure(k: &[u8; 32], iv: &[u8; 32]) -> std::io::Result<SecureBox<Self>> {
+        selftest::power_on();
+        // All-zero is a valid Hc256.
+        let mut cipher: SecureBox<Self> = unsafe { SecureBox::zeroed()? };
+        let state = &mut *cipher;
+        setup::key_setup(&mut state.p, &mut state.q, k, iv);
+        Ok(cipher)
+    }
+
     pub fn set_state(&mut self, k: &[u8; 32], iv: &[u8; 32], offset: usize) {
-        set_cipher_tables!(self, k, iv);
+        setup::key_setup(&mut self.p, &mut self.q, k, iv);
+        self.i = 0;
+        self.pos = 0;
+
         for _ in 0..((offset / 4)+((offset % 4 != 0) as usize) ) {
             self.gen_word();
         }
@@ -103,12 +105,57 @@ impl Hc256 {
         pad.zeroize();
     }
 
+    /// Copies the full cipher state, both copies continue with the same
+    /// keystream.
+    ///
+    /// Encrypting different data with both copies reuses keystream and
+    /// exposes the XOR of the plaintexts. Only meant for trying several
+    /// decryptions of the same ciphertext. Each copy zeroizes on drop.
+    #[cfg(feature = "fork")]
+    pub fn fork_unchecked(&self) -> Self {
+        Hc256 { p: self.p, q: self.q, i: self.i, pos: self.pos }
+    }
+
+    /// XORs keystream words into `dest`. Same result as `apply_stream` over
+    /// the words' little-endian bytes, without converting.
+    pub fn apply_stream_words(&mut self, dest: &mut [u32]) {
+        for word in dest.iter_mut() {
+            *word ^= self.gen_word();
+        }
+    }
+
+    /// Overwrites `dest` with keystream, consuming it like `apply_stream`.
+    pub fn fill_keystream(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+        self.apply_stream(dest);
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.gen_word()
+    }
+
+    pub fn keystream_bytes(&mut self) -> KeystreamBytes<'_, Self> {
+        KeystreamBytes::new(self)
+    }
+
+    pub fn keystream_words(&mut self) -> KeystreamWords<'_, Self> {
+        KeystreamWords::new(self)
+    }
+
+    /// The `p` and `q` tables, for comparing against other implementations
+    /// when debugging test vectors.
+    #[cfg(feature = "debug-tables")]
+    pub fn debug_tables(&self) -> (&[u32; 1024], &[u32; 1024]) {
+        (&self.p, &self.q)
+    }
+
     #[inline]
-    fn gen_word(&mut self) -> u32 {
+    pub(crate) fn gen_word(&mut self) -> u32 {
         let i = self.i;
         let (j, j3, j10, j12, j1023) = self.offsets();
 
         self.i = (self.i + 1) & (2048 - 1);
+        self.pos += 1;
 
         if i < 1024 {
             self.p[j] = self.p[j]
@@ -166,4 +213,46 @@ impl Hc256 {
             self.i.wrapping_add(1) & 1023
         )
     }
-}
\ No newline at end of file
+}
+
+// Never shows the tables, they are the key schedule. `position` counts
+// keystream words since keying, `table_index` is the step within the
+// 2048-step P/Q cycle and wraps.
+impl fmt::Debug for Hc256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hc256")
+            .field("algorithm", &"HC-256")
+            .field("variant", &"word")
+            .field("position", &self.pos)
+            .field("table_index", &self.i)
+            .finish()
+    }
+}
+
+impl keystream::sealed::Source for Hc256 {
+    fn next_u32(&mut self) -> u32 {
+        self.gen_word()
+    }
+
+    fn take_pending(&mut self) -> ([u8; 4], usize) {
+        ([0; 4], 4)
+    }
+
+    fn restore(&mut self, _rest: &[u8]) {}
+}
+
+#[cfg(feature = "serde")]
+impl Hc256 {
+    pub(crate) fn to_raw(&self) -> material::RawState {
+        material::RawState { p: self.p.to_vec(), q: self.q.to_vec(), i: self.i, r: [0; 3], c: 0, pos: self.pos }
+    }
+
+    // `raw` has passed `RawState::check`.
+    pub(crate) fn from_raw(raw: &material::RawState) -> Self {
+        selftest::power_on();
+        let mut cipher = Hc256 { p: [0; 1024], q: [0; 1024], i: raw.i, pos: raw.pos };
+        cipher.p.copy_from_slice(&raw.p);
+        cipher.q.copy_from_slice(&raw.q);
+        cipher
+    }
+}
@@ -0,0 +1,81 @@
+use super::*;
+use crate::aead::derive_iv;
+
+/// How a sector's IV is derived from its number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SectorIv {
+    /// The sector number is mixed directly into the base IV.
+    Plain64,
+    /// The sector number is encrypted under a key derived from the main key,
+    /// so IVs cannot be predicted without it.
+    Essiv,
+}
+
+/// Encrypts fixed-size sectors that can each be decrypted on their own.
+///
+/// Sectors carry no tag, encrypting and decrypting are the same operation.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct SectorCipher {
+    k: [u8; 32],
+    iv: [u8; 32],
+    essiv: [u8; 32],
+    #[zeroize(skip)]
+    mode: SectorIv,
+    sector_size: usize,
+}
+
+impl SectorCipher {
+    pub fn new(k: &[u8; 32], iv: &[u8; 32], mode: SectorIv, sector_size: usize) -> Self {
+        assert!(sector_size > 0, "Sector size must not be zero");
+
+        let mut essiv: [u8; 32] = [0; 32];
+        if mode == SectorIv::Essiv {
+            let mut salt_iv = derive_iv(iv, u64::MAX, 0);
+            Hc256::new(k, &salt_iv).apply_stream(&mut essiv);
+            salt_iv.zeroize();
+        }
+
+        SectorCipher { k: *k, iv: *iv, essiv, mode, sector_size }
+    }
+
+    pub fn sector_size(&self) -> usize {
+        self.sector_size
+    }
+
+    pub fn mode(&self) -> SectorIv {
+        self.mode
+    }
+
+    pub fn sector_iv(&self, sector: u64) -> [u8; 32] {
+        match self.mode {
+            SectorIv::Plain64 => derive_iv(&self.iv, 0, sector),
+            SectorIv::Essiv => {
+                let mut iv: [u8; 32] = [0; 32];
+                Hc256::new(&self.essiv, &derive_iv(&[0; 32], 0, sector)).apply_stream(&mut iv);
+                iv
+            }
+        }
+    }
+
+    /// Encrypts or decrypts one sector, `data` may be shorter than a sector.
+    pub fn apply_sector(&self, sector: u64, data: &mut [u8]) {
+        assert!(data.len() <= self.sector_size, "Data is larger than a sector");
+
+        let mut iv = self.sector_iv(sector);
+        Hc256::new(&self.k, &iv).apply_stream(data);
+        iv.zeroize();
+    }
+
+    /// Processes consecutive sectors starting at `first` on all available cores.
+    pub fn apply_range(&self, first: u64, data: &mut [u8]) {
+        self.apply_range_threads(first, data, threads::available());
+    }
+
+    pub fn apply_range_threads(&self, first: u64, data: &mut [u8], threads: usize) {
+        threads::par_chunks_mut(data, self.sector_size, threads, |i, sector| {
+            self.apply_sector(first + i as u64, sector);
+        });
+    }
+}
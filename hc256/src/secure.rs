@@ -0,0 +1,84 @@
+use std::io;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+use super::*;
+
+/// Owning pointer to cipher state kept in locked, non-dumpable pages.
+///
+/// The pages are `mlock`ed so the tables never reach swap and marked
+/// `MADV_DONTDUMP` so they are left out of core dumps. On drop the state is
+/// zeroized before the pages are unlocked and unmapped.
+pub struct SecureBox<T: Zeroize> {
+    ptr: NonNull<T>,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Zeroize + Send> Send for SecureBox<T> {}
+unsafe impl<T: Zeroize + Sync> Sync for SecureBox<T> {}
+
+impl<T: Zeroize> SecureBox<T> {
+    // Maps zero-filled pages for a `T`. Only sound for types where all zero
+    // bytes is a valid value.
+    pub(crate) unsafe fn zeroed() -> io::Result<Self> {
+        let page = match libc::sysconf(libc::_SC_PAGESIZE) {
+            n if n > 0 => n as usize,
+            _ => return Err(io::Error::last_os_error()),
+        };
+        let len = std::mem::size_of::<T>().max(1).div_ceil(page) * page;
+
+        let addr = libc::mmap(
+            ptr::null_mut(),
+            len,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        if libc::mlock(addr, len) != 0 {
+            let err = io::Error::last_os_error();
+            libc::munmap(addr, len);
+            return Err(err);
+        }
+        if libc::madvise(addr, len, libc::MADV_DONTDUMP) != 0 {
+            let err = io::Error::last_os_error();
+            libc::munlock(addr, len);
+            libc::munmap(addr, len);
+            return Err(err);
+        }
+
+        Ok(SecureBox { ptr: NonNull::new_unchecked(addr.cast()), len, _marker: PhantomData })
+    }
+}
+
+impl<T: Zeroize> Deref for SecureBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.ptr.as_ref() }
+    }
+}
+
+impl<T: Zeroize> DerefMut for SecureBox<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { self.ptr.as_mut() }
+    }
+}
+
+impl<T: Zeroize> Drop for SecureBox<T> {
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(self.ptr.as_ptr());
+            // Also covers padding and the rest of the last page.
+            std::slice::from_raw_parts_mut(self.ptr.as_ptr().cast::<u8>(), self.len).zeroize();
+            libc::munlock(self.ptr.as_ptr().cast(), self.len);
+            libc::munmap(self.ptr.as_ptr().cast(), self.len);
+        }
+    }
+}
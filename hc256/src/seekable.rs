@@ -0,0 +1,369 @@
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use zeroize::Zeroizing;
+
+use super::*;
+use crate::aead::{derive_iv, TAG_LEN};
+
+pub const MAGIC: [u8; 8] = *b"HC256SK\x01";
+
+pub const HEADER_LEN: usize = 16;
+
+/// Per-chunk prefix holding the random generation the chunk was written with.
+pub const GEN_LEN: usize = 8;
+
+pub const DEFAULT_CHUNK_SIZE: u32 = 65536;
+
+/// Splits data into fixed-size chunks, each encrypted and authenticated on its
+/// own so any byte can be reached with a single chunk read.
+///
+/// The file is a header followed by chunks of `gen || ciphertext || tag`. A
+/// chunk's IV is derived from the base IV, its index and a random generation
+/// that changes on every rewrite, so rewritten chunks never reuse keystream.
+/// The last chunk is marked final in its tag, truncation at a chunk boundary
+/// is detected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::material::nonzero"))]
+    pub chunk_size: u32,
+}
+
+impl Header {
+    pub fn new(chunk_size: u32) -> Self {
+        assert!(chunk_size > 0, "Chunk size must not be zero");
+        Header { chunk_size }
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut bytes: [u8; HEADER_LEN] = [0; HEADER_LEN];
+        bytes[..8].copy_from_slice(&MAGIC);
+        bytes[8..12].copy_from_slice(&self.chunk_size.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; HEADER_LEN]) -> io::Result<Self> {
+        if bytes[..8] != MAGIC {
+            return Err(invalid("not a seekable hc256 file"));
+        }
+
+        let chunk_size = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        if chunk_size == 0 || bytes[12..] != [0; 4] {
+            return Err(invalid("unsupported seekable header"));
+        }
+
+        Ok(Header { chunk_size })
+    }
+
+    /// Bytes a chunk occupies on disk.
+    pub fn stride(&self) -> u64 {
+        (GEN_LEN + self.chunk_size as usize + TAG_LEN) as u64
+    }
+
+    // Returns the chunk count and the plaintext length of the last chunk.
+    fn layout(&self, file_len: u64) -> io::Result<(u64, usize)> {
+        let body = file_len.checked_sub(HEADER_LEN as u64).ok_or_else(|| invalid("file is truncated"))?;
+        let full = body / self.stride();
+        let rest = body % self.stride();
+
+        if rest == 0 && full > 0 {
+            Ok((full, self.chunk_size as usize))
+        } else if rest >= (GEN_LEN + TAG_LEN) as u64 {
+            Ok((full + 1, rest as usize - GEN_LEN - TAG_LEN))
+        } else {
+            Err(invalid("file is truncated"))
+        }
+    }
+}
+
+struct Keys {
+    k: Zeroizing<[u8; 32]>,
+    iv: Zeroizing<[u8; 32]>,
+    header: Header,
+}
+
+impl Keys {
+    fn aad(&self, index: u64, gen: u64, last: bool) -> [u8; HEADER_LEN + 17] {
+        let mut aad: [u8; HEADER_LEN + 17] = [0; HEADER_LEN + 17];
+        aad[..HEADER_LEN].copy_from_slice(&self.header.to_bytes());
+        aad[HEADER_LEN..HEADER_LEN + 8].copy_from_slice(&index.to_le_bytes());
+        aad[HEADER_LEN + 8..HEADER_LEN + 16].copy_from_slice(&gen.to_le_bytes());
+        aad[HEADER_LEN + 16] = last as u8;
+        aad
+    }
+
+    fn offset(&self, index: u64) -> u64 {
+        HEADER_LEN as u64 + index * self.header.stride()
+    }
+
+    fn read_chunk<R: Read + Seek>(&self, inner: &mut R, index: u64, len: usize, last: bool) -> io::Result<Zeroizing<Vec<u8>>> {
+        let mut raw = vec![0; GEN_LEN + len + TAG_LEN];
+        inner.seek(SeekFrom::Start(self.offset(index)))?;
+        inner.read_exact(&mut raw)?;
+
+        let gen = u64::from_le_bytes(raw[..GEN_LEN].try_into().unwrap());
+        let mut data = Zeroizing::new(raw[GEN_LEN..GEN_LEN + len].to_vec());
+        let iv = Zeroizing::new(derive_iv(&self.iv, gen, index));
+
+        if aead::open(&self.k, &iv, &self.aad(index, gen, last), &mut data, &raw[GEN_LEN + len..]) {
+            Ok(data)
+        } else {
+            Err(invalid("chunk failed authentication"))
+        }
+    }
+
+    fn write_chunk<W: Write + Seek>(&self, inner: &mut W, index: u64, data: &[u8], last: bool) -> io::Result<()> {
+        let mut gen: [u8; GEN_LEN] = [0; GEN_LEN];
+        getrandom::getrandom(&mut gen)?;
+        let gen = u64::from_le_bytes(gen);
+
+        let mut raw = Vec::with_capacity(GEN_LEN + data.len() + TAG_LEN);
+        raw.extend_from_slice(&gen.to_le_bytes());
+        raw.extend_from_slice(data);
+
+        let iv = Zeroizing::new(derive_iv(&self.iv, gen, index));
+        let tag = aead::seal(&self.k, &iv, &self.aad(index, gen, last), &mut raw[GEN_LEN..]);
+        raw.extend_from_slice(&tag);
+
+        inner.seek(SeekFrom::Start(self.offset(index)))?;
+        inner.write_all(&raw)
+    }
+}
+
+/// Random-access reader over a seekable file, seeking costs one chunk read.
+pub struct SeekableReader<R> {
+    inner: R,
+    keys: Keys,
+    chunks: u64,
+    last_len: usize,
+    pos: u64,
+    cached: Option<(u64, Zeroizing<Vec<u8>>)>,
+}
+
+impl<R: Read + Seek> SeekableReader<R> {
+    pub fn new(mut inner: R, k: &[u8; 32], iv: &[u8; 32]) -> io::Result<Self> {
+        let header = read_header(&mut inner)?;
+        let (chunks, last_len) = header.layout(inner.seek(SeekFrom::End(0))?)?;
+
+        Ok(SeekableReader {
+            inner,
+            keys: Keys { k: Zeroizing::new(*k), iv: Zeroizing::new(*iv), header },
+            chunks,
+            last_len,
+            pos: 0,
+            cached: None,
+        })
+    }
+
+    pub fn header(&self) -> Header {
+        self.keys.header
+    }
+
+    pub fn chunk_count(&self) -> u64 {
+        self.chunks
+    }
+
+    /// Plaintext length.
+    pub fn len(&self) -> u64 {
+        (self.chunks - 1) * self.keys.header.chunk_size as u64 + self.last_len as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Decrypts and verifies a single chunk.
+    pub fn read_chunk(&mut self, index: u64) -> io::Result<Zeroizing<Vec<u8>>> {
+        if index >= self.chunks {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "chunk index out of range"));
+        }
+
+        let last = index == self.chunks - 1;
+        let len = if last { self.last_len } else { self.keys.header.chunk_size as usize };
+        self.keys.read_chunk(&mut self.inner, index, len, last)
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read + Seek> Read for SeekableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.pos >= self.len() {
+            return Ok(0);
+        }
+
+        let chunk_size = self.keys.header.chunk_size as u64;
+        let index = self.pos / chunk_size;
+        let start = (self.pos % chunk_size) as usize;
+
+        if !matches!(&self.cached, Some((i, _)) if *i == index) {
+            self.cached = Some((index, self.read_chunk(index)?));
+        }
+
+        let chunk = &self.cached.as_ref().unwrap().1;
+        let n = buf.len().min(chunk.len() - start);
+        buf[..n].copy_from_slice(&chunk[start..start + n]);
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for SeekableReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(d) => self.len().checked_add_signed(d),
+            SeekFrom::Current(d) => self.pos.checked_add_signed(d),
+        };
+
+        match target {
+            Some(p) => {
+                self.pos = p;
+                Ok(p)
+            }
+            None => Err(io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative position")),
+        }
+    }
+}
+
+/// Creates, appends to and rewrites chunks of a seekable file.
+///
+/// The partial last chunk is kept in memory and written on [`flush`], which
+/// [`finish`] and drop call.
+///
+/// [`flush`]: Write::flush
+/// [`finish`]: SeekableWriter::finish
+pub struct SeekableWriter<F: Read + Write + Seek> {
+    inner: Option<F>,
+    keys: Keys,
+    chunks: u64,
+    tail: Zeroizing<Vec<u8>>,
+}
+
+impl<F: Read + Write + Seek> SeekableWriter<F> {
+    /// Starts a new file in `inner`, which must be empty.
+    pub fn create(mut inner: F, k: &[u8; 32], iv: &[u8; 32], chunk_size: u32) -> io::Result<Self> {
+        let header = Header::new(chunk_size);
+        // Old bytes past the new end would be read back as chunks.
+        if inner.seek(SeekFrom::End(0))? != 0 {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "file is not empty"));
+        }
+        inner.write_all(&header.to_bytes())?;
+
+        let mut writer = SeekableWriter {
+            inner: Some(inner),
+            keys: Keys { k: Zeroizing::new(*k), iv: Zeroizing::new(*iv), header },
+            chunks: 1,
+            tail: Zeroizing::new(Vec::new()),
+        };
+        writer.flush()?;
+
+        Ok(writer)
+    }
+
+    /// Opens an existing file for appending and rewriting.
+    pub fn open(mut inner: F, k: &[u8; 32], iv: &[u8; 32]) -> io::Result<Self> {
+        let header = read_header(&mut inner)?;
+        let (chunks, last_len) = header.layout(inner.seek(SeekFrom::End(0))?)?;
+        let keys = Keys { k: Zeroizing::new(*k), iv: Zeroizing::new(*iv), header };
+        let tail = keys.read_chunk(&mut inner, chunks - 1, last_len, true)?;
+
+        Ok(SeekableWriter { inner: Some(inner), keys, chunks, tail })
+    }
+
+    pub fn chunk_count(&self) -> u64 {
+        self.chunks
+    }
+
+    /// Plaintext length, including data not yet flushed.
+    pub fn len(&self) -> u64 {
+        (self.chunks - 1) * self.keys.header.chunk_size as u64 + self.tail.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends to the end of the file.
+    pub fn append(&mut self, mut data: &[u8]) -> io::Result<()> {
+        let chunk_size = self.keys.header.chunk_size as usize;
+
+        while !data.is_empty() {
+            if self.tail.len() == chunk_size {
+                let inner = self.inner.as_mut().unwrap();
+                self.keys.write_chunk(inner, self.chunks - 1, &self.tail, false)?;
+                self.tail.clear();
+                self.chunks += 1;
+            }
+
+            let n = data.len().min(chunk_size - self.tail.len());
+            self.tail.extend_from_slice(&data[..n]);
+            data = &data[n..];
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the contents of one chunk, `data` must be exactly as long as
+    /// the chunk currently is.
+    pub fn rewrite_chunk(&mut self, index: u64, data: &[u8]) -> io::Result<()> {
+        if index >= self.chunks {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "chunk index out of range"));
+        }
+
+        if index == self.chunks - 1 {
+            if data.len() != self.tail.len() {
+                return Err(io::Error::new(ErrorKind::InvalidInput, "data length does not match the chunk"));
+            }
+            self.tail.copy_from_slice(data);
+            return self.flush();
+        }
+
+        if data.len() != self.keys.header.chunk_size as usize {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "data length does not match the chunk"));
+        }
+
+        self.keys.write_chunk(self.inner.as_mut().unwrap(), index, data, false)
+    }
+
+    /// Flushes the last chunk and returns the underlying file.
+    pub fn finish(mut self) -> io::Result<F> {
+        self.flush()?;
+        Ok(self.inner.take().unwrap())
+    }
+}
+
+impl<F: Read + Write + Seek> Write for SeekableWriter<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.append(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let inner = self.inner.as_mut().unwrap();
+        self.keys.write_chunk(inner, self.chunks - 1, &self.tail, true)?;
+        inner.flush()
+    }
+}
+
+impl<F: Read + Write + Seek> Drop for SeekableWriter<F> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.flush();
+        }
+    }
+}
+
+fn read_header<R: Read + Seek>(inner: &mut R) -> io::Result<Header> {
+    let mut bytes: [u8; HEADER_LEN] = [0; HEADER_LEN];
+    inner.seek(SeekFrom::Start(0))?;
+    inner.read_exact(&mut bytes)?;
+    Header::from_bytes(&bytes)
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg)
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::fmt;
+
+use super::*;
+
+// Names of the `Hc256` and split `BufHc256` runs, key, IV and the first 32
+// keystream bytes.
+type Vector = (&'static str, &'static str, [u8; 32], [u8; 32], [u8; 32]);
+
+// The published HC-256 vectors.
+const VECTORS: [Vector; 3] = [
+    ("vector 1", "vector 1 split across words", [0; 32], [0; 32], [
+        0x5b, 0x07, 0x89, 0x85, 0xd8, 0xf6, 0xf3, 0x0d,
+        0x42, 0xc5, 0xc0, 0x2f, 0xa6, 0xb6, 0x79, 0x51,
+        0x53, 0xf0, 0x65, 0x34, 0x80, 0x1f, 0x89, 0xf2,
+        0x4e, 0x74, 0x24, 0x8b, 0x72, 0x0b, 0x48, 0x18,
+    ]),
+    ("vector 2", "vector 2 split across words", [0; 32], one_hot(0x01), [
+        0xaf, 0xe2, 0xa2, 0xbf, 0x4f, 0x17, 0xce, 0xe9,
+        0xfe, 0xc2, 0x05, 0x8b, 0xd1, 0xb1, 0x8b, 0xb1,
+        0x5f, 0xc0, 0x42, 0xee, 0x71, 0x2b, 0x31, 0x01,
+        0xdd, 0x50, 0x1f, 0xc6, 0x0b, 0x08, 0x2a, 0x50,
+    ]),
+    ("vector 3", "vector 3 split across words", one_hot(0x55), [0; 32], [
+        0x1c, 0x40, 0x4a, 0xfe, 0x4f, 0xe2, 0x5f, 0xed,
+        0x95, 0x8f, 0x9a, 0xd1, 0xae, 0x36, 0xc0, 0x6f,
+        0x88, 0xa6, 0x5a, 0x3c, 0xc0, 0xab, 0xe2, 0x23,
+        0xae, 0xb3, 0x90, 0x2f, 0x42, 0x0e, 0xd3, 0xa8,
+    ]),
+];
+
+// Chunk sizes fed to `BufHc256` so every split offset within a word is hit.
+const SPLITS: [usize; 8] = [1, 2, 3, 5, 4, 7, 6, 4];
+
+/// A known-answer test that produced the wrong keystream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestError {
+    pub test: &'static str,
+    pub expected: Vec<u8>,
+    pub actual: Vec<u8>,
+}
+
+impl fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let at = self.expected.iter().zip(&self.actual).position(|(e, a)| e != a).unwrap_or(0);
+        write!(f, "HC-256 self test {} failed at byte {}", self.test, at)
+    }
+}
+
+impl Error for SelfTestError {}
+
+/// Runs the published known-answer vectors through `Hc256` and `BufHc256`,
+/// the latter split across word boundaries, and reports the first mismatch.
+pub fn self_test() -> Result<(), SelfTestError> {
+    for (name, split_name, k, iv, expected) in VECTORS.iter() {
+        let mut actual = [0; 32];
+        Hc256::new_unchecked(k, iv).apply_stream(&mut actual);
+        check(name, expected, &actual)?;
+
+        let mut actual = [0; 32];
+        let mut cipher = BufHc256::new_unchecked(k, iv);
+        let mut start = 0;
+        for len in SPLITS {
+            cipher.apply_stream(&mut actual[start..start + len]);
+            start += len;
+        }
+        check(split_name, expected, &actual)?;
+    }
+
+    Ok(())
+}
+
+// Runs `self_test` once per process when the `self-test` feature is on and
+// refuses to hand out a cipher if it failed.
+#[inline]
+pub(crate) fn power_on() {
+    #[cfg(feature = "self-test")]
+    {
+        static RESULT: std::sync::OnceLock<Result<(), SelfTestError>> = std::sync::OnceLock::new();
+
+        if let Err(e) = RESULT.get_or_init(self_test) {
+            panic!("Refusing to operate: {}", e);
+        }
+    }
+}
+
+fn check(test: &'static str, expected: &[u8], actual: &[u8]) -> Result<(), SelfTestError> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(SelfTestError { test, expected: expected.to_vec(), actual: actual.to_vec() })
+    }
+}
+
+const fn one_hot(first: u8) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[0] = first;
+    bytes
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::fmt;
+
+use super::*;
+use crate::aead::{derive_iv, TAG_LEN};
+
+/// Length of the little-endian sequence number prefixed to every frame.
+pub const SEQ_LEN: usize = 8;
+
+/// Bytes a sealed frame adds on top of the message.
+pub const OVERHEAD: usize = SEQ_LEN + TAG_LEN;
+
+/// Number of sequence numbers below the highest accepted one that are still
+/// tracked for replays.
+pub const WINDOW_SIZE: u64 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionError {
+    /// The frame is shorter than [`OVERHEAD`].
+    Truncated,
+    /// The tag did not match, the frame was forged or corrupted.
+    Authentication,
+    /// The sequence number was already accepted.
+    Replayed(u64),
+    /// The sequence number fell behind the replay window.
+    TooOld(u64),
+    /// The sealer ran out of sequence numbers and must be re-keyed.
+    Exhausted,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Truncated => write!(f, "frame is shorter than {} bytes", OVERHEAD),
+            SessionError::Authentication => write!(f, "frame failed authentication"),
+            SessionError::Replayed(seq) => write!(f, "frame {} was already received", seq),
+            SessionError::TooOld(seq) => write!(f, "frame {} is outside the replay window", seq),
+            SessionError::Exhausted => write!(f, "sequence numbers exhausted, re-key the session"),
+        }
+    }
+}
+
+impl Error for SessionError {}
+
+/// Encrypts a sequence of messages under one key, deriving each message's IV
+/// from the base IV and its sequence number.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct Sealer {
+    k: [u8; 32],
+    iv: [u8; 32],
+    seq: u64,
+}
+
+impl Sealer {
+    pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> Self {
+        Sealer { k: *k, iv: *iv, seq: 0 }
+    }
+
+    /// Sequence number the next sealed frame will carry.
+    pub fn sequence(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns `seq || ciphertext || tag`.
+    pub fn seal(&mut self, msg: &[u8]) -> Result<Vec<u8>, SessionError> {
+        if self.seq == u64::MAX {
+            return Err(SessionError::Exhausted);
+        }
+
+        let seq = self.seq;
+        self.seq += 1;
+
+        let mut frame = Vec::with_capacity(msg.len() + OVERHEAD);
+        frame.extend_from_slice(&seq.to_le_bytes());
+        frame.extend_from_slice(msg);
+
+        let mut iv = derive_iv(&self.iv, 0, seq);
+        let (header, body) = frame.split_at_mut(SEQ_LEN);
+        let tag = aead::seal(&self.k, &iv, header, body);
+        iv.zeroize();
+
+        frame.extend_from_slice(&tag);
+        Ok(frame)
+    }
+}
+
+/// Sliding window over the highest accepted sequence number.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayWindow {
+    next: u64,
+    seen: u64,
+}
+
+impl ReplayWindow {
+    pub fn new() -> Self {
+        ReplayWindow::default()
+    }
+
+    /// Checks `seq` without recording it.
+    pub fn check(&self, seq: u64) -> Result<(), SessionError> {
+        if seq >= self.next {
+            return Ok(());
+        }
+
+        let age = self.next - 1 - seq;
+        if age >= WINDOW_SIZE {
+            Err(SessionError::TooOld(seq))
+        } else if self.seen & (1 << age) != 0 {
+            Err(SessionError::Replayed(seq))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Records `seq` as received, it must have passed [`ReplayWindow::check`].
+    pub fn accept(&mut self, seq: u64) {
+        if seq >= self.next {
+            let shift = seq - self.next + 1;
+            self.seen = if shift >= WINDOW_SIZE { 0 } else { self.seen << shift };
+            self.seen |= 1;
+            self.next = seq + 1;
+        } else {
+            self.seen |= 1 << (self.next - 1 - seq);
+        }
+    }
+}
+
+/// Authenticates and decrypts frames produced by a [`Sealer`] with the same
+/// key and IV, rejecting duplicates and frames older than the window.
+#[derive(Zeroize)]
+#[zeroize(drop)]
+pub struct Opener {
+    k: [u8; 32],
+    iv: [u8; 32],
+    #[zeroize(skip)]
+    window: ReplayWindow,
+}
+
+impl Opener {
+    pub fn new(k: &[u8; 32], iv: &[u8; 32]) -> Self {
+        Opener { k: *k, iv: *iv, window: ReplayWindow::new() }
+    }
+
+    pub fn open(&mut self, frame: &[u8]) -> Result<Vec<u8>, SessionError> {
+        if frame.len() < OVERHEAD {
+            return Err(SessionError::Truncated);
+        }
+
+        let (header, rest) = frame.split_at(SEQ_LEN);
+        let (body, tag) = rest.split_at(rest.len() - TAG_LEN);
+        let seq = u64::from_le_bytes(header.try_into().unwrap());
+
+        self.window.check(seq)?;
+
+        let mut msg = body.to_vec();
+        let mut iv = derive_iv(&self.iv, 0, seq);
+        let valid = aead::open(&self.k, &iv, header, &mut msg, tag);
+        iv.zeroize();
+
+        if !valid {
+            return Err(SessionError::Authentication);
+        }
+
+        self.window.accept(seq);
+        Ok(msg)
+    }
+}
@@ -0,0 +1,119 @@
+use super::*;
+
+// One warm-up update of `$t[$j]`. `$m3`, `$m2` and `$m1` hold the three
+// previously updated words so `t[j - 3]` never waits on a store.
+macro_rules! warm_up_step {
+    ($t:ident, $other:ident, $j:expr, $m3:ident, $m2:ident, $m1:ident) => {
+        let j = $j;
+        let v = $t[j]
+            .wrapping_add($t[j.wrapping_sub(10) & 1023])
+            .wrapping_add(g($other, $m3, $t[(j + 1) & 1023]));
+        $t[j] = v;
+        $m3 = $m2;
+        $m2 = $m1;
+        $m1 = v;
+    };
+}
+
+// Computes W[$i] into the 16-word window `$w` and evaluates to it.
+macro_rules! expand_step {
+    ($w:ident, $i:expr, $w2:ident, $w1:ident) => {{
+        let i = $i;
+        let v = f2($w2)
+            .wrapping_add($w[(i - 7) & 15])
+            .wrapping_add(f1($w[(i - 15) & 15]))
+            .wrapping_add($w[i & 15])
+            .wrapping_add(i as u32);
+        $w[i & 15] = v;
+        $w2 = $w1;
+        $w1 = v;
+        v
+    }};
+}
+
+/// Fills `p` and `q` from the key and IV and runs the 4096 warm-up steps.
+///
+/// The expansion `W` is never materialised: each word only depends on the 16
+/// before it, so a 16-word window covers `W[0..512]` and the first words of
+/// each table, after which the rest is computed in place from the table
+/// itself. Peak stack use stays at a few hundred bytes.
+///
+/// Warm-up output is discarded, so the steps skip `h1`/`h2` entirely and run
+/// as four branch-free, unrolled passes alternating over `p` and `q` instead
+/// of 4096 calls to `gen_word`. Leaves the tables as if `i` had wrapped back
+/// to 0.
+pub(crate) fn key_setup(p: &mut TABLE, q: &mut TABLE, k: &[u8; 32], iv: &[u8; 32]) {
+    let mut w: [u32; 16] = [0; 16];
+
+    for i in 0..8 {
+        w[i] = u32::from_le_bytes(k[4 * i..4 * i + 4].try_into().unwrap());
+        w[i + 8] = u32::from_le_bytes(iv[4 * i..4 * i + 4].try_into().unwrap());
+    }
+
+    // W[i - 2] and W[i - 1] stay in registers, they are the dependency chain.
+    // W[i - 16] sits in the slot W[i] is about to take.
+    let (mut w2, mut w1) = (w[14], w[15]);
+    for i in 16..512 {
+        expand_step!(w, i, w2, w1);
+    }
+    for i in 512..528 {
+        p[i - 512] = expand_step!(w, i, w2, w1);
+    }
+    expand_table(p, 512, w2, w1);
+
+    // Restart the window from the tail of `p` for the first words of `q`.
+    w.copy_from_slice(&p[1008..]);
+    (w2, w1) = (p[1022], p[1023]);
+    for i in 1536..1552 {
+        q[i - 1536] = expand_step!(w, i, w2, w1);
+    }
+    expand_table(q, 1536, w2, w1);
+
+    w.zeroize();
+    w2.zeroize();
+    w1.zeroize();
+
+    for _ in 0..2 {
+        warm_up(p, q);
+        warm_up(q, p);
+    }
+}
+
+// Computes `t[16..]` in place as `W[base + 16..base + 1024]`, `t[..16]` must
+// already hold the words before them.
+fn expand_table(t: &mut TABLE, base: usize, mut w2: u32, mut w1: u32) {
+    for j in 16..1024 {
+        let v = f2(w2)
+            .wrapping_add(t[j - 7])
+            .wrapping_add(f1(t[j - 15]))
+            .wrapping_add(t[j - 16])
+            .wrapping_add((base + j) as u32);
+        t[j] = v;
+        w2 = w1;
+        w1 = v;
+    }
+}
+
+// One pass of 1024 table updates on `t`, `other` is the table `g1`/`g2` read.
+#[inline(never)]
+fn warm_up(t: &mut TABLE, other: &TABLE) {
+    let (mut m3, mut m2, mut m1) = (t[1021], t[1022], t[1023]);
+
+    let mut j = 0;
+    while j < 1024 {
+        warm_up_step!(t, other, j, m3, m2, m1);
+        warm_up_step!(t, other, j + 1, m3, m2, m1);
+        warm_up_step!(t, other, j + 2, m3, m2, m1);
+        warm_up_step!(t, other, j + 3, m3, m2, m1);
+        warm_up_step!(t, other, j + 4, m3, m2, m1);
+        warm_up_step!(t, other, j + 5, m3, m2, m1);
+        warm_up_step!(t, other, j + 6, m3, m2, m1);
+        warm_up_step!(t, other, j + 7, m3, m2, m1);
+        j += 8;
+    }
+}
+
+#[inline(always)]
+fn g(other: &TABLE, x: u32, y: u32) -> u32 {
+    (x.rotate_right(10) ^ y.rotate_right(23)).wrapping_add(other[(x ^ y) as usize & 1023])
+}
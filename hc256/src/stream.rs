@@ -0,0 +1,241 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use zeroize::Zeroizing;
+
+use super::*;
+use crate::aead::TAG_LEN;
+
+/// Largest plaintext carried by a single record.
+pub const MAX_RECORD: usize = 16384;
+
+const NONCE_LEN: usize = 32;
+const LEN_LEN: usize = 2;
+// Set in the authenticated length of the empty record sent by `finish`.
+const FINAL: u16 = 0x8000;
+
+/// An authenticated, encrypted channel over any `Read + Write` transport.
+///
+/// Both ends share a pre-shared key. During the handshake each side sends a
+/// random nonce, from which independent send and receive keystreams are
+/// derived. Every record is `len || ciphertext || tag`, with the one-time
+/// Poly1305 key for the tag taken from the direction's keystream.
+///
+/// A sender ends its direction with `finish`. The transport closing before
+/// that final record arrives is reported as `UnexpectedEof`, so dropped
+/// trailing records are never mistaken for a clean end of stream.
+pub struct SecureStream<S> {
+    inner: S,
+    send: Box<BufHc256>,
+    recv: Box<BufHc256>,
+    pending: Zeroizing<Vec<u8>>,
+    pos: usize,
+    sent_final: bool,
+    got_final: bool,
+}
+
+impl<S: Read + Write> SecureStream<S> {
+    /// Performs the client side of the handshake.
+    pub fn connect(mut inner: S, psk: &[u8; 32]) -> io::Result<Self> {
+        let mut ours: [u8; NONCE_LEN] = [0; NONCE_LEN];
+        random(&mut ours)?;
+        inner.write_all(&ours)?;
+        inner.flush()?;
+
+        let mut theirs: [u8; NONCE_LEN] = [0; NONCE_LEN];
+        inner.read_exact(&mut theirs)?;
+
+        let (c2s, s2c) = derive_ivs(psk, &ours, &theirs);
+        let stream = SecureStream::with_ivs(inner, psk, c2s, s2c);
+        stream.confirm()
+    }
+
+    /// Performs the server side of the handshake.
+    pub fn accept(mut inner: S, psk: &[u8; 32]) -> io::Result<Self> {
+        let mut theirs: [u8; NONCE_LEN] = [0; NONCE_LEN];
+        inner.read_exact(&mut theirs)?;
+
+        let mut ours: [u8; NONCE_LEN] = [0; NONCE_LEN];
+        random(&mut ours)?;
+        inner.write_all(&ours)?;
+        inner.flush()?;
+
+        let (c2s, s2c) = derive_ivs(psk, &theirs, &ours);
+        let stream = SecureStream::with_ivs(inner, psk, s2c, c2s);
+        stream.confirm()
+    }
+
+    /// Sends the final record and flushes. Later writes fail, the peer reads
+    /// end of stream once it has everything sent before this.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.sent_final {
+            self.send_frame(FINAL, &[])?;
+            self.sent_final = true;
+        }
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn with_ivs(inner: S, psk: &[u8; 32], mut send: [u8; 32], mut recv: [u8; 32]) -> Self {
+        let stream = SecureStream {
+            inner,
+            send: Box::new(BufHc256::new(psk, &send)),
+            recv: Box::new(BufHc256::new(psk, &recv)),
+            pending: Zeroizing::new(Vec::new()),
+            pos: 0,
+            sent_final: false,
+            got_final: false,
+        };
+        send.zeroize();
+        recv.zeroize();
+        stream
+    }
+
+    // Both sides open with an empty record so a mismatched key fails the
+    // handshake instead of the first read.
+    fn confirm(mut self) -> io::Result<Self> {
+        self.send_record(&[])?;
+        self.inner.flush()?;
+
+        match self.recv_record() {
+            Ok(true) if self.pending.is_empty() => Ok(self),
+            Ok(_) => Err(io::Error::new(ErrorKind::InvalidData, "handshake did not complete")),
+            Err(e) if e.kind() == ErrorKind::InvalidData => Err(io::Error::new(
+                ErrorKind::PermissionDenied,
+                "handshake failed, pre-shared keys do not match",
+            )),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn send_record(&mut self, data: &[u8]) -> io::Result<()> {
+        if self.sent_final {
+            return Err(io::Error::new(ErrorKind::BrokenPipe, "stream already finished"));
+        }
+        self.send_frame(data.len() as u16, data)
+    }
+
+    fn send_frame(&mut self, len: u16, data: &[u8]) -> io::Result<()> {
+        let header = len.to_le_bytes();
+
+        let mut otk: [u8; 32] = [0; 32];
+        self.send.apply_stream(&mut otk);
+
+        let mut record = Vec::with_capacity(LEN_LEN + data.len() + TAG_LEN);
+        record.extend_from_slice(&header);
+        record.extend_from_slice(data);
+        self.send.apply_stream(&mut record[LEN_LEN..]);
+
+        let tag = aead::authenticate(&otk, &header, &record[LEN_LEN..]);
+        otk.zeroize();
+        record.extend_from_slice(&tag);
+
+        self.inner.write_all(&record)
+    }
+
+    // Returns `false` once the peer's final record has been verified.
+    fn recv_record(&mut self) -> io::Result<bool> {
+        let mut header: [u8; LEN_LEN] = [0; LEN_LEN];
+        match self.inner.read_exact(&mut header) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, "stream truncated before its final record"))
+            }
+            r => r?,
+        }
+
+        let word = u16::from_le_bytes(header);
+        let last = word & FINAL != 0;
+        let len = (word & !FINAL) as usize;
+        if last && len != 0 {
+            return Err(io::Error::new(ErrorKind::InvalidData, "final record carries data"));
+        }
+        if len > MAX_RECORD {
+            return Err(io::Error::new(ErrorKind::InvalidData, "record exceeds maximum length"));
+        }
+
+        let mut body = vec![0; len + TAG_LEN];
+        self.inner.read_exact(&mut body)?;
+        let tag = body.split_off(len);
+
+        let mut otk: [u8; 32] = [0; 32];
+        self.recv.apply_stream(&mut otk);
+        let valid = aead::verify(&otk, &header, &body, &tag);
+        otk.zeroize();
+
+        if !valid {
+            return Err(io::Error::new(ErrorKind::InvalidData, "record failed authentication"));
+        }
+
+        self.recv.apply_stream(&mut body);
+        self.pending = Zeroizing::new(body);
+        self.pos = 0;
+
+        Ok(!last)
+    }
+}
+
+impl<S: Read + Write> Read for SecureStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.pending.len() {
+            if buf.is_empty() || self.got_final {
+                return Ok(0);
+            }
+            self.got_final = !self.recv_record()?;
+        }
+
+        let n = buf.len().min(self.pending.len() - self.pos);
+        buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pending[self.pos..self.pos + n].zeroize();
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+impl<S: Read + Write> Write for SecureStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let n = buf.len().min(MAX_RECORD);
+        self.send_record(&buf[..n])?;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn random(dest: &mut [u8]) -> io::Result<()> {
+    getrandom::getrandom(dest).map_err(io::Error::from)
+}
+
+// Mixes both nonces under the key, then expands them into one IV per
+// direction so a reflected nonce still yields distinct streams.
+fn derive_ivs(psk: &[u8; 32], client: &[u8; 32], server: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut mix = *server;
+    Hc256::new(psk, client).apply_stream(&mut mix);
+
+    let mut ivs: [u8; 64] = [0; 64];
+    Hc256::new(psk, &mix).apply_stream(&mut ivs);
+    mix.zeroize();
+
+    let c2s: [u8; 32] = ivs[..32].try_into().unwrap();
+    let s2c: [u8; 32] = ivs[32..].try_into().unwrap();
+    ivs.zeroize();
+
+    (c2s, s2c)
+}
@@ -0,0 +1,35 @@
+use std::num::NonZeroUsize;
+use std::thread;
+
+pub(crate) fn available() -> usize {
+    thread::available_parallelism().map_or(1, NonZeroUsize::get)
+}
+
+// Splits `data` into `chunk`-sized pieces and hands contiguous runs of them to
+// at most `threads` scoped workers. `f` receives each piece's index.
+pub(crate) fn par_chunks_mut<F>(data: &mut [u8], chunk: usize, threads: usize, f: F)
+where
+    F: Fn(usize, &mut [u8]) + Sync,
+{
+    let count = data.len().div_ceil(chunk);
+    let threads = threads.clamp(1, count.max(1));
+
+    if threads == 1 {
+        for (i, piece) in data.chunks_mut(chunk).enumerate() {
+            f(i, piece);
+        }
+        return;
+    }
+
+    let per_thread = count.div_ceil(threads);
+    thread::scope(|s| {
+        for (t, run) in data.chunks_mut(per_thread * chunk).enumerate() {
+            let f = &f;
+            s.spawn(move || {
+                for (i, piece) in run.chunks_mut(chunk).enumerate() {
+                    f(t * per_thread + i, piece);
+                }
+            });
+        }
+    });
+}
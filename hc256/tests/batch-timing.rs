@@ -0,0 +1,55 @@
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+use hc256::{Hc256, Hc256x4};
+
+#[test]
+fn batch_stream_time() {
+    let keys: [[u8; 32]; 4] = [[1; 32], [2; 32], [3; 32], [4; 32]];
+    let ivs: [[u8; 32]; 4] = [[5; 32], [6; 32], [7; 32], [8; 32]];
+
+    let mut data: [[u8; 16384]; 4] = [[0; 16384]; 4];
+
+    let mut singles: Vec<Hc256> = (0..4).map(|i| Hc256::new(&keys[i], &ivs[i])).collect();
+    let mut batch = Hc256x4::new(&keys, &ivs);
+
+    // Warmup
+    for _ in 0..1000 {
+        for (cipher, buf) in singles.iter_mut().zip(data.iter_mut()) {
+            cipher.apply_stream(buf);
+        }
+        let [a, b, c, d] = &mut data;
+        batch.apply_streams(&mut [a, b, c, d]);
+    }
+
+    // Run test
+    let single_time = Instant::now();
+    for _ in 0..32768 {
+        for (cipher, buf) in singles.iter_mut().zip(data.iter_mut()) {
+            cipher.apply_stream(buf);
+        }
+    }
+    let single_time = single_time.elapsed().as_nanos();
+
+    let batch_time = Instant::now();
+    for _ in 0..32768 {
+        let [a, b, c, d] = &mut data;
+        batch.apply_streams(&mut [a, b, c, d]);
+    }
+    let batch_time = batch_time.elapsed().as_nanos();
+
+    let bytes = 32768f64 * 4.0 * 16384.0;
+    let info_string = format!(
+        "-------------------------------------\nData\n-------------------------------------\nSeparate total time:  {}\nBatched total time:   {}\nSeparate ns per byte: {}\nBatched ns per byte:  {}\nSpeedup:              {}\n-------------------------------------\n"
+        , single_time
+        , batch_time
+        , single_time as f64 / bytes
+        , batch_time as f64 / bytes
+        , single_time as f64 / batch_time as f64
+    );
+
+    std::fs::create_dir_all("timing").unwrap();
+    let mut file = File::create("timing/batch-stream-time-info-4_3GB").unwrap();
+    file.write_all(info_string.as_bytes()).unwrap();
+}
@@ -0,0 +1,75 @@
+use hc256::{Hc256, Hc256x4, Hc256x8};
+
+fn keys<const N: usize>(seed: u8) -> [[u8; 32]; N] {
+    let mut keys = [[0; 32]; N];
+    for (i, k) in keys.iter_mut().enumerate() {
+        k.iter_mut().enumerate().for_each(|(j, b)| *b = seed ^ (i * 31 + j) as u8);
+    }
+    keys
+}
+
+#[test]
+fn lanes_match_separate_ciphers() {
+    let k = keys::<4>(1);
+    let iv = keys::<4>(2);
+    let lens = [64, 13, 1000, 0];
+
+    let mut batched: Vec<Vec<u8>> = lens.iter().map(|&l| vec![0xa5; l]).collect();
+    let mut expected = batched.clone();
+
+    let mut cipher = Hc256x4::new(&k, &iv);
+    for _ in 0..3 {
+        let [a, b, c, d] = &mut batched[..] else { unreachable!() };
+        cipher.apply_streams(&mut [&mut a[..], &mut b[..], &mut c[..], &mut d[..]]);
+    }
+
+    for (i, buf) in expected.iter_mut().enumerate() {
+        let mut single = Hc256::new(&k[i], &iv[i]);
+        for _ in 0..3 {
+            single.apply_stream(buf);
+        }
+    }
+    assert_eq!(batched, expected);
+}
+
+#[test]
+fn encrypt_eight_in_one_call() {
+    let k = keys::<8>(3);
+    let iv = keys::<8>(4);
+    let mut bufs = [[0u8; 37]; 8];
+
+    {
+        let [a, b, c, d, e, f, g, h] = &mut bufs;
+        Hc256x8::encrypt(&k, &iv, &mut [a, b, c, d, e, f, g, h]);
+    }
+
+    for (i, buf) in bufs.iter().enumerate() {
+        let mut expected = [0u8; 37];
+        Hc256::new(&k[i], &iv[i]).apply_stream(&mut expected);
+        assert_eq!(buf, &expected);
+    }
+}
+
+#[test]
+fn short_lane_does_not_stall_the_others() {
+    let k = keys::<4>(5);
+    let iv = keys::<4>(6);
+    let lens = [3, 1000, 0, 517];
+
+    let mut batched: Vec<Vec<u8>> = lens.iter().map(|&l| vec![0x3c; l]).collect();
+    let mut expected = batched.clone();
+
+    let mut cipher = Hc256x4::new(&k, &iv);
+    for _ in 0..2 {
+        let [a, b, c, d] = &mut batched[..] else { unreachable!() };
+        cipher.apply_streams(&mut [&mut a[..], &mut b[..], &mut c[..], &mut d[..]]);
+    }
+
+    for (i, buf) in expected.iter_mut().enumerate() {
+        let mut single = Hc256::new(&k[i], &iv[i]);
+        for _ in 0..2 {
+            single.apply_stream(buf);
+        }
+    }
+    assert_eq!(batched, expected);
+}
@@ -0,0 +1,39 @@
+use hc256::{BufHc256, Hc256};
+
+#[test]
+fn new_boxed_matches_new() {
+    let k = [0x55; 32];
+    let iv = [0x77; 32];
+    let mut expected = [0; 1027];
+    Hc256::new(&k, &iv).apply_stream(&mut expected);
+
+    let mut result = [0; 1027];
+    Hc256::new_boxed(&k, &iv).apply_stream(&mut result);
+    assert_eq!(result, expected);
+
+    let mut result = [0; 1027];
+    let mut cipher = BufHc256::new_boxed(&k, &iv);
+    cipher.apply_stream(&mut result[..5]);
+    cipher.apply_stream(&mut result[5..]);
+    assert_eq!(result, expected);
+}
+
+#[cfg(all(feature = "secure-memory", target_os = "linux"))]
+#[test]
+fn new_secure_matches_new() {
+    let k = [0x55; 32];
+    let iv = [0x77; 32];
+    let mut expected = [0; 1027];
+    Hc256::new(&k, &iv).apply_stream(&mut expected);
+
+    let mut result = [0; 1027];
+    Hc256::new_secure(&k, &iv).unwrap().apply_stream(&mut result);
+    assert_eq!(result, expected);
+
+    let mut result = [0; 1027];
+    let mut cipher = BufHc256::new_secure(&k, &iv).unwrap();
+    cipher.apply_stream(&mut result[..5]);
+    cipher.set_state(&k, &iv, 5);
+    cipher.apply_stream(&mut result[5..]);
+    assert_eq!(result, expected);
+}
@@ -153,4 +153,18 @@ fn split_vector_3() {
         0x95, 0x8f, 0x9a, 0xd1, 0xae, 0x36, 0xc0, 0x6f,
         0x88, 0xa6, 0x5a, 0x3c, 0xc0, 0xab, 0xe2, 0x23,
         0xae, 0xb3, 0x90, 0x2f, 0x42, 0x0e, 0xd3, 0xa8, ]);
-}
\ No newline at end of file
+}
+
+#[test]
+fn set_state_vector_1() {
+    let mut cipher = BufHc256::new(&[0x55; 32], &[0x77; 32]);
+    cipher.apply_stream(&mut [0; 3]);
+    cipher.set_state(&[0; 32], &[0; 32], 0);
+    let mut result: [u8; 32] = [0; 32];
+
+    cipher.apply_stream(&mut result);
+    assert_eq!(result, [0x5b, 0x07, 0x89, 0x85, 0xd8, 0xf6, 0xf3, 0x0d,
+        0x42, 0xc5, 0xc0, 0x2f, 0xa6, 0xb6, 0x79, 0x51,
+        0x53, 0xf0, 0x65, 0x34, 0x80, 0x1f, 0x89, 0xf2,
+        0x4e, 0x74, 0x24, 0x8b, 0x72, 0x0b, 0x48, 0x18, ]);
+}
@@ -0,0 +1,77 @@
+use std::io::ErrorKind;
+use std::net::UdpSocket;
+
+use hc256::datagram::{new_session_id, PacketOpener, PacketSealer, SecureUdpSocket, OVERHEAD};
+use hc256::session::SessionError;
+
+fn connected_pair() -> (UdpSocket, UdpSocket) {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    a.connect(b.local_addr().unwrap()).unwrap();
+    b.connect(a.local_addr().unwrap()).unwrap();
+    (a, b)
+}
+
+#[test]
+fn packets_open_out_of_order() {
+    let id = [1; 16];
+    let mut sealer = PacketSealer::new(&[2; 32], &id);
+    let mut opener = PacketOpener::new(&[2; 32], &id);
+
+    let packets: Vec<Vec<u8>> = (0..4u8).map(|i| sealer.seal(&[i; 10]).unwrap()).collect();
+    assert_eq!(packets[0].len(), 10 + OVERHEAD);
+
+    for i in [3, 1, 0] {
+        assert_eq!(opener.open(&packets[i]).unwrap(), [i as u8; 10]);
+    }
+    assert_eq!(opener.open(&packets[1]), Err(SessionError::Replayed(1)));
+    assert_eq!(sealer.packet_number(), 4);
+}
+
+#[test]
+fn session_id_is_bound() {
+    let mut sealer = PacketSealer::new(&[2; 32], &[1; 16]);
+    let mut opener = PacketOpener::new(&[2; 32], &[9; 16]);
+    let packet = sealer.seal(b"telemetry").unwrap();
+
+    assert_eq!(opener.open(&packet), Err(SessionError::Authentication));
+}
+
+#[test]
+fn loopback_round_trip() {
+    let k = [5; 32];
+    let (a_id, b_id) = (new_session_id().unwrap(), new_session_id().unwrap());
+    let (a, b) = connected_pair();
+    let mut a = SecureUdpSocket::new(a, &k, &a_id, &b_id);
+    let mut b = SecureUdpSocket::new(b, &k, &b_id, &a_id);
+
+    let mut buf = [0; 64];
+    a.send(b"ping").unwrap();
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"ping");
+
+    b.send(b"pong").unwrap();
+    let (n, from) = a.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"pong");
+    assert_eq!(from, b.get_ref().local_addr().unwrap());
+}
+
+#[test]
+fn loopback_replay_is_rejected() {
+    let k = [6; 32];
+    let (a_id, b_id) = ([1; 16], [2; 16]);
+    let (raw, b) = connected_pair();
+    let mut b = SecureUdpSocket::new(b, &k, &b_id, &a_id);
+
+    let packet = PacketSealer::new(&k, &a_id).seal(b"reading").unwrap();
+    raw.send(&packet).unwrap();
+    raw.send(&packet).unwrap();
+
+    let mut buf = [0; 64];
+    let n = b.recv(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"reading");
+
+    let err = b.recv(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(err.into_inner().unwrap().downcast::<SessionError>().unwrap().as_ref(), &SessionError::Replayed(0));
+}
@@ -0,0 +1,57 @@
+use hc256::{BufHc256, Hc256};
+
+#[test]
+fn debug_is_redacted() {
+    let mut cipher = Hc256::new(&[0; 32], &[0; 32]);
+    cipher.apply_stream(&mut [0; 8]);
+    assert_eq!(format!("{:?}", cipher), r#"Hc256 { algorithm: "HC-256", variant: "word", position: 2, table_index: 2 }"#);
+
+    let mut cipher = BufHc256::new(&[0; 32], &[0; 32]);
+    cipher.apply_stream(&mut [0; 7]);
+    assert_eq!(
+        format!("{:?}", cipher),
+        r#"BufHc256 { algorithm: "HC-256", variant: "byte-buffered", position: 7, table_index: 2, buffered: 1 }"#
+    );
+}
+
+#[test]
+fn table_index_wraps() {
+    let mut cipher = Hc256::new(&[0; 32], &[0; 32]);
+    cipher.apply_stream(&mut [0; 2049 * 4]);
+    assert!(format!("{:?}", cipher).contains("position: 2049, table_index: 1 }"));
+}
+
+#[test]
+fn position_counts_every_path() {
+    let mut cipher = BufHc256::new(&[0; 32], &[0; 32]);
+    cipher.apply_stream(&mut [0; 3]);
+    cipher.next_u32();
+    cipher.apply_stream_words(&mut [0; 2]);
+    cipher.keystream_bytes().take(5).for_each(drop);
+    cipher.fill_keystream(&mut [0; 8193]);
+    assert!(format!("{:?}", cipher).contains("position: 8213,"));
+
+    cipher.set_state(&[0; 32], &[0; 32], 10);
+    assert!(format!("{:?}", cipher).contains("position: 10,"));
+
+    let mut cipher = Hc256::new(&[0; 32], &[0; 32]);
+    cipher.next_u32();
+    cipher.keystream_words().take(3).for_each(drop);
+    assert!(format!("{:?}", cipher).contains("position: 4,"));
+
+    cipher.set_state(&[0; 32], &[0; 32], 8);
+    assert!(format!("{:?}", cipher).contains("position: 2,"));
+}
+
+#[cfg(feature = "debug-tables")]
+#[test]
+fn tables_match_between_variants() {
+    let mut word = Hc256::new(&[9; 32], &[1; 32]);
+    let mut byte = BufHc256::new(&[9; 32], &[1; 32]);
+    word.apply_stream(&mut [0; 4000]);
+    byte.apply_stream(&mut [0; 4000]);
+
+    let (p, q) = word.debug_tables();
+    assert_eq!(byte.debug_tables(), (p, q));
+    assert_ne!(p, q);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "fork")]
+
+use std::mem::MaybeUninit;
+use std::ptr;
+
+use hc256::{BufHc256, Hc256};
+
+#[test]
+fn fork_continues_same_stream() {
+    let mut cipher = Hc256::new(&[7; 32], &[8; 32]);
+    cipher.apply_stream(&mut [0; 40]);
+
+    let mut fork = cipher.fork_unchecked();
+    assert_eq!(format!("{:?}", fork), format!("{:?}", cipher));
+    let mut a = [0; 100];
+    let mut b = [0; 100];
+    cipher.apply_stream(&mut a);
+    fork.apply_stream(&mut b);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn fork_keeps_buffered_bytes() {
+    let ciphertext = {
+        let mut data = *b"speculative decryption of a partially read message";
+        BufHc256::new(&[7; 32], &[8; 32]).apply_stream(&mut data);
+        data
+    };
+
+    let mut cipher = BufHc256::new(&[7; 32], &[8; 32]);
+    let mut head = ciphertext;
+    cipher.apply_stream(&mut head[..13]);
+
+    let mut tries = Vec::new();
+    for _ in 0..3 {
+        let mut attempt = cipher.fork_unchecked();
+        let mut rest = ciphertext;
+        attempt.apply_stream(&mut rest[13..]);
+        tries.push(rest[13..].to_vec());
+    }
+
+    let mut rest = ciphertext;
+    cipher.apply_stream(&mut rest[13..]);
+    assert!(tries.iter().all(|t| t[..] == rest[13..]));
+    assert_eq!(&rest[13..], &b"speculative decryption of a partially read message"[13..]);
+}
+
+// An all-zero state only ever produces zero keystream, which identifies a
+// wiped fork without comparing against its private tables.
+#[test]
+fn dropped_fork_is_zeroized() {
+    let mut cipher = Hc256::new(&[7; 32], &[8; 32]);
+    let mut slot = MaybeUninit::new(cipher.fork_unchecked());
+    unsafe { ptr::drop_in_place(slot.as_mut_ptr()) };
+
+    // Every field is plain data, so the wiped bytes are still a valid state
+    // and `MaybeUninit` never drops it again.
+    let mut keystream = [0xff; 64];
+    unsafe { slot.assume_init_mut() }.fill_keystream(&mut keystream);
+    assert_eq!(keystream, [0; 64]);
+
+    let mut expected = [0; 64];
+    Hc256::new(&[7; 32], &[8; 32]).fill_keystream(&mut expected);
+    cipher.fill_keystream(&mut keystream);
+    assert_eq!(keystream, expected);
+}
+
+#[test]
+fn dropped_buffered_fork_is_zeroized() {
+    let mut cipher = BufHc256::new(&[7; 32], &[8; 32]);
+    cipher.apply_stream(&mut [0; 5]);
+    let mut slot = MaybeUninit::new(cipher.fork_unchecked());
+    unsafe { ptr::drop_in_place(slot.as_mut_ptr()) };
+
+    let mut keystream = [0xff; 64];
+    unsafe { slot.assume_init_mut() }.fill_keystream(&mut keystream);
+    assert_eq!(keystream, [0; 64]);
+
+    let mut expected = [0; 69];
+    BufHc256::new(&[7; 32], &[8; 32]).fill_keystream(&mut expected);
+    cipher.fill_keystream(&mut keystream);
+    assert_eq!(keystream, expected[5..]);
+}
@@ -0,0 +1,104 @@
+use hc256::{BufHc256, Hc256};
+
+fn expected(len: usize) -> Vec<u8> {
+    let mut stream = vec![0; len];
+    BufHc256::new(&[5; 32], &[6; 32]).apply_stream(&mut stream);
+    stream
+}
+
+#[test]
+fn fill_keystream_overwrites() {
+    let mut dest = [0xff; 37];
+    Hc256::new(&[5; 32], &[6; 32]).fill_keystream(&mut dest);
+    assert_eq!(dest.to_vec(), expected(37));
+
+    let mut dest = [0xff; 37];
+    let mut cipher = BufHc256::new(&[5; 32], &[6; 32]);
+    cipher.fill_keystream(&mut dest[..3]);
+    cipher.fill_keystream(&mut dest[3..]);
+    assert_eq!(dest.to_vec(), expected(37));
+}
+
+#[test]
+fn next_u32_is_little_endian() {
+    let stream = expected(12);
+
+    let mut cipher = Hc256::new(&[5; 32], &[6; 32]);
+    assert_eq!(cipher.next_u32(), u32::from_le_bytes(stream[0..4].try_into().unwrap()));
+    assert_eq!(cipher.next_u32(), u32::from_le_bytes(stream[4..8].try_into().unwrap()));
+
+    let mut cipher = BufHc256::new(&[5; 32], &[6; 32]);
+    cipher.apply_stream(&mut [0; 3]);
+    assert_eq!(cipher.next_u32(), u32::from_le_bytes(stream[3..7].try_into().unwrap()));
+    assert_eq!(cipher.next_u32(), u32::from_le_bytes(stream[7..11].try_into().unwrap()));
+}
+
+#[test]
+fn iterators_match_stream() {
+    let stream = expected(4100);
+
+    let mut cipher = Hc256::new(&[5; 32], &[6; 32]);
+    assert_eq!(cipher.keystream_bytes().take(4100).collect::<Vec<_>>(), stream);
+
+    let mut cipher = BufHc256::new(&[5; 32], &[6; 32]);
+    let words: Vec<u8> = cipher.keystream_words().take(1025).flat_map(u32::to_le_bytes).collect();
+    assert_eq!(words, stream);
+}
+
+#[test]
+fn buffered_bytes_survive_iterator() {
+    let stream = expected(20);
+    let mut cipher = BufHc256::new(&[5; 32], &[6; 32]);
+    let mut result = vec![0; 2];
+
+    cipher.apply_stream(&mut result);
+    result.extend(cipher.keystream_bytes().take(5));
+    result.extend(cipher.keystream_bytes().take(1));
+    let mut rest = [0; 12];
+    cipher.apply_stream(&mut rest);
+    result.extend_from_slice(&rest);
+
+    assert_eq!(result, stream);
+}
+
+#[test]
+fn unbuffered_bytes_discard_partial_word() {
+    let stream = expected(12);
+    let mut cipher = Hc256::new(&[5; 32], &[6; 32]);
+
+    assert_eq!(cipher.keystream_bytes().take(5).collect::<Vec<_>>(), stream[..5]);
+    assert_eq!(cipher.next_u32(), u32::from_le_bytes(stream[8..12].try_into().unwrap()));
+}
+
+#[test]
+fn apply_stream_words_matches_bytes() {
+    let words: Vec<u32> = (0..300u32).map(|n| n.wrapping_mul(0x9e37_79b9)).collect();
+    let mut expected_bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+    BufHc256::new(&[5; 32], &[6; 32]).apply_stream(&mut expected_bytes);
+
+    let mut result = words.clone();
+    let mut cipher = Hc256::new(&[5; 32], &[6; 32]);
+    cipher.apply_stream_words(&mut result[..7]);
+    cipher.apply_stream_words(&mut result[7..]);
+    assert_eq!(result.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<_>>(), expected_bytes);
+
+    let mut result = words.clone();
+    BufHc256::new(&[5; 32], &[6; 32]).apply_stream_words(&mut result);
+    assert_eq!(result.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<_>>(), expected_bytes);
+}
+
+#[test]
+fn apply_stream_words_after_unaligned_bytes() {
+    let mut expected = [0; 42];
+    BufHc256::new(&[5; 32], &[6; 32]).apply_stream(&mut expected);
+
+    let mut cipher = BufHc256::new(&[5; 32], &[6; 32]);
+    let mut head = [0; 2];
+    cipher.apply_stream(&mut head);
+    let mut words = [0u32; 10];
+    cipher.apply_stream_words(&mut words);
+
+    let mut result = head.to_vec();
+    result.extend(words.iter().flat_map(|w| w.to_le_bytes()));
+    assert_eq!(result, expected);
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "serde")]
+
+use serde::{Deserialize, Serialize};
+
+use hc256::material::{ExposedState, Iv, Key};
+use hc256::sector::SectorIv;
+use hc256::{parallel, seekable, BufHc256, Hc256};
+
+#[derive(Serialize, Deserialize)]
+struct Config {
+    key: Key,
+    #[serde(with = "hc256::material::hex")]
+    iv: Iv,
+    header: seekable::Header,
+    sector_iv: SectorIv,
+}
+
+#[test]
+fn config_round_trip() {
+    let config = Config {
+        key: Key([0xab; 32]),
+        iv: Iv::from([0x01; 32]),
+        header: seekable::Header::new(4096),
+        sector_iv: SectorIv::Essiv,
+    };
+
+    let json = serde_json::to_string(&config).unwrap();
+    assert_eq!(
+        json,
+        format!(
+            r#"{{"key":"{}","iv":"{}","header":{{"chunk_size":4096}},"sector_iv":"Essiv"}}"#,
+            "q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=",
+            "01".repeat(32)
+        )
+    );
+
+    let parsed: Config = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.key, config.key);
+    assert_eq!(parsed.iv, config.iv);
+    assert_eq!(parsed.header, config.header);
+    assert_eq!(parsed.sector_iv, config.sector_iv);
+}
+
+#[test]
+fn key_accepts_hex_or_base64() {
+    let from_hex: Key = serde_json::from_str(&format!("\"{}\"", "AB".repeat(32))).unwrap();
+    let from_base64: Key = serde_json::from_str("\"q6urq6urq6urq6urq6urq6urq6urq6urq6urq6urq6s=\"").unwrap();
+    assert_eq!(from_hex.as_bytes(), &[0xab; 32]);
+    assert_eq!(from_base64.as_bytes(), &[0xab; 32]);
+
+    assert!(serde_json::from_str::<Key>("\"q6urq6ur\"").is_err());
+    assert!(serde_json::from_str::<Key>(&format!("\"{}\"", "0".repeat(63))).is_err());
+    assert!(serde_json::from_str::<Key>("\"not base64!\"").is_err());
+    assert_eq!(format!("{:?}", from_hex), "Key(..)");
+}
+
+#[test]
+fn zero_size_headers_rejected() {
+    assert!(serde_json::from_str::<seekable::Header>(r#"{"chunk_size":0}"#).is_err());
+    assert!(serde_json::from_str::<parallel::Header>(r#"{"segment_size":0}"#).is_err());
+    assert!(serde_json::from_str::<parallel::Header>(r#"{"segment_size":18446744073709551615}"#).is_err());
+    let header: parallel::Header = serde_json::from_str(r#"{"segment_size":65536}"#).unwrap();
+    assert_eq!(header, parallel::Header::new(65536));
+}
+
+#[test]
+fn exposed_state_resumes_stream() {
+    let mut expected = [0; 64];
+    BufHc256::new(&[3; 32], &[4; 32]).apply_stream(&mut expected);
+
+    let mut cipher = BufHc256::new(&[3; 32], &[4; 32]);
+    let mut result = [0; 64];
+    cipher.apply_stream(&mut result[..21]);
+    let debug = format!("{:?}", cipher);
+    let json = serde_json::to_string(&ExposedState(cipher)).unwrap();
+
+    let ExposedState(mut resumed) = serde_json::from_str::<ExposedState<BufHc256>>(&json).unwrap();
+    assert_eq!(format!("{:?}", resumed), debug);
+    resumed.apply_stream(&mut result[21..]);
+    assert_eq!(result, expected);
+
+    let mut cipher = Hc256::new(&[3; 32], &[4; 32]);
+    let mut result = [0; 64];
+    cipher.apply_stream(&mut result[..20]);
+    let json = serde_json::to_string(&ExposedState(cipher)).unwrap();
+    let ExposedState(mut resumed) = serde_json::from_str::<ExposedState<Hc256>>(&json).unwrap();
+    resumed.apply_stream(&mut result[20..]);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn exposed_state_validated() {
+    assert!(serde_json::from_str::<ExposedState<Hc256>>(r#"{"p":[1,2],"q":[3],"i":0}"#).is_err());
+
+    let json = serde_json::to_string(&ExposedState(Hc256::new(&[0; 32], &[0; 32]))).unwrap();
+    let json = json.replace(r#""i":0"#, r#""i":4096"#);
+    assert!(serde_json::from_str::<ExposedState<Hc256>>(&json).is_err());
+}
+
+#[test]
+fn equality_compares_every_byte() {
+    let mut last = [0x11; 32];
+    last[31] ^= 1;
+    assert_eq!(Key::from([0x11; 32]), Key::from([0x11; 32]));
+    assert_ne!(Key::from([0x11; 32]), Key::from(last));
+    assert_ne!(Iv::from([0x11; 32]), Iv::from(last));
+}
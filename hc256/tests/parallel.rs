@@ -0,0 +1,78 @@
+use hc256::parallel::{Header, ParallelCipher, HEADER_LEN, MAX_SEGMENT_SIZE};
+use hc256::sector::{SectorCipher, SectorIv};
+use hc256::Hc256;
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 13) as u8).collect()
+}
+
+#[test]
+fn thread_count_does_not_change_output() {
+    let cipher = ParallelCipher::new(&[1; 32], &[2; 32], Header::new(1000));
+    let mut one = data(10_500);
+    let mut many = one.clone();
+
+    cipher.apply_threads(0, &mut one, 1);
+    cipher.apply_threads(0, &mut many, 8);
+    assert_eq!(one, many);
+
+    cipher.apply(0, &mut many);
+    assert_eq!(many, data(10_500));
+}
+
+#[test]
+fn segments_match_individual_ciphers() {
+    let cipher = ParallelCipher::new(&[3; 32], &[4; 32], Header::new(64));
+    let mut all = [0; 200];
+    cipher.apply(0, &mut all);
+
+    for (i, segment) in all.chunks(64).enumerate() {
+        let mut expected = vec![0; segment.len()];
+        Hc256::new(&[3; 32], &cipher.segment_iv(i as u64)).apply_stream(&mut expected);
+        assert_eq!(segment, &expected[..]);
+    }
+    assert_ne!(all[..64], all[64..128]);
+}
+
+#[test]
+fn batches_can_start_mid_file() {
+    let cipher = ParallelCipher::new(&[5; 32], &[6; 32], Header::new(128));
+    let mut whole = data(1024);
+    cipher.apply(0, &mut whole);
+
+    let mut second_half = data(1024)[512..].to_vec();
+    cipher.apply(4, &mut second_half);
+    assert_eq!(second_half, whole[512..]);
+}
+
+#[test]
+fn header_round_trip() {
+    let header = Header::new(4096);
+    let bytes = header.to_bytes();
+    assert_eq!(bytes.len(), HEADER_LEN);
+    assert_eq!(Header::from_bytes(&bytes).unwrap(), header);
+    assert!(Header::from_bytes(&[0; HEADER_LEN]).is_err());
+
+    let mut oversized = Header::new(MAX_SEGMENT_SIZE).to_bytes();
+    assert!(Header::from_bytes(&oversized).is_ok());
+    oversized[8..].copy_from_slice(&(MAX_SEGMENT_SIZE + 1).to_le_bytes());
+    assert!(Header::from_bytes(&oversized).is_err());
+}
+
+#[test]
+fn segment_ivs_are_domain_separated() {
+    let (k, iv) = ([7; 32], [8; 32]);
+    let cipher = ParallelCipher::new(&k, &iv, Header::new(512));
+    assert_ne!(cipher.segment_iv(0), iv);
+
+    let sectors = SectorCipher::new(&k, &iv, SectorIv::Plain64, 512);
+    for i in 0..4 {
+        assert_ne!(cipher.segment_iv(i), sectors.sector_iv(i));
+    }
+
+    let mut segment = [0; 64];
+    cipher.apply_segment(0, &mut segment);
+    let mut plain = [0; 64];
+    Hc256::new(&k, &iv).apply_stream(&mut plain);
+    assert_ne!(segment, plain);
+}
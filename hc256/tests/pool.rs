@@ -0,0 +1,88 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use hc256::{BufHc256, CipherPool};
+
+#[test]
+fn pooled_matches_new() {
+    let pool = CipherPool::new(2);
+
+    for seed in 0..5u8 {
+        let mut cipher = pool.get(&[seed; 32], &[!seed; 32]);
+        let mut result = [0; 100];
+        cipher.apply_stream(&mut result[..3]);
+        cipher.apply_stream(&mut result[3..]);
+
+        let mut expected = [0; 100];
+        BufHc256::new(&[seed; 32], &[!seed; 32]).apply_stream(&mut expected);
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn bounded_by_capacity() {
+    let pool = CipherPool::new(2);
+    let a = pool.get(&[1; 32], &[1; 32]);
+    let _b = pool.try_get(&[2; 32], &[2; 32]).unwrap();
+    assert_eq!(pool.available(), 0);
+    assert!(pool.try_get(&[3; 32], &[3; 32]).is_none());
+
+    drop(a);
+    assert_eq!(pool.available(), 1);
+    assert!(pool.try_get(&[3; 32], &[3; 32]).is_some());
+    assert_eq!(pool.available(), 1);
+}
+
+#[test]
+fn get_waits_for_return() {
+    let pool = CipherPool::new(1);
+    let held = pool.get(&[1; 32], &[1; 32]);
+
+    let (tx, rx) = mpsc::channel();
+    let waiter = {
+        let pool = pool.clone();
+        thread::spawn(move || {
+            let mut cipher = pool.get(&[2; 32], &[2; 32]);
+            let mut result = [0; 16];
+            cipher.apply_stream(&mut result);
+            tx.send(result).unwrap();
+        })
+    };
+
+    assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+    drop(held);
+
+    let mut expected = [0; 16];
+    BufHc256::new(&[2; 32], &[2; 32]).apply_stream(&mut expected);
+    assert_eq!(rx.recv().unwrap(), expected);
+    waiter.join().unwrap();
+    assert_eq!(pool.available(), 1);
+}
+
+#[test]
+fn shared_across_threads() {
+    let pool = CipherPool::new(3);
+
+    let workers: Vec<_> = (0..8u8)
+        .map(|t| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                for n in 0..20u8 {
+                    let mut cipher = pool.get(&[t; 32], &[n; 32]);
+                    let mut result = [0; 32];
+                    cipher.apply_stream(&mut result);
+
+                    let mut expected = [0; 32];
+                    BufHc256::new(&[t; 32], &[n; 32]).apply_stream(&mut expected);
+                    assert_eq!(result, expected);
+                }
+            })
+        })
+        .collect();
+
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert_eq!(pool.available(), pool.capacity());
+}
@@ -0,0 +1,46 @@
+use hc256::{BufHc256, PrefetchingHc256};
+
+#[test]
+fn matches_buffered_cipher() {
+    let mut prefetch = PrefetchingHc256::with_capacity(&[1; 32], &[2; 32], 100).unwrap();
+    let mut buf = BufHc256::new(&[1; 32], &[2; 32]);
+
+    for len in [1, 3, 0, 250, 7, 4096, 2, 99] {
+        let mut a = vec![0x3c; len];
+        let mut b = a.clone();
+        prefetch.apply_stream(&mut a);
+        buf.apply_stream(&mut b);
+        assert_eq!(a, b);
+    }
+}
+
+#[test]
+fn metrics_track_consumption() {
+    let mut prefetch = PrefetchingHc256::with_capacity(&[3; 32], &[4; 32], 4096).unwrap();
+    prefetch.apply_stream(&mut [0; 5000]);
+
+    let metrics = prefetch.metrics();
+    assert_eq!(metrics.capacity, 4096);
+    assert_eq!(metrics.consumed, 5000);
+    assert!(metrics.depth <= 4096);
+    assert!(metrics.stalls >= 1);
+}
+
+#[test]
+fn buffer_fills_ahead_of_use() {
+    let prefetch = PrefetchingHc256::with_capacity(&[5; 32], &[6; 32], 2048).unwrap();
+
+    for _ in 0..1000 {
+        if prefetch.metrics().depth == 2048 {
+            return;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    panic!("Prefetch buffer never filled");
+}
+
+#[test]
+fn zero_capacity_is_an_error() {
+    let err = PrefetchingHc256::with_capacity(&[7; 32], &[8; 32], 0).err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
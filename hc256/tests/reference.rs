@@ -0,0 +1,49 @@
+#![cfg(feature = "reference")]
+
+use rand::{Rng, RngCore};
+
+use hc256::reference::{differential_check, Granularity, Hc256Ref, Mismatch};
+use hc256::{BufHc256, Hc256, PrefetchingHc256};
+
+#[test]
+fn reference_vector_1() {
+    let mut cipher = Hc256Ref::new(&[0; 32], &[0; 32]);
+    assert_eq!(cipher.next_word().to_le_bytes(), [0x5b, 0x07, 0x89, 0x85]);
+    assert_eq!(cipher.next_word().to_le_bytes(), [0xd8, 0xf6, 0xf3, 0x0d]);
+}
+
+#[test]
+fn random_splits() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..8 {
+        let mut k = [0; 32];
+        let mut iv = [0; 32];
+        rng.fill_bytes(&mut k);
+        rng.fill_bytes(&mut iv);
+        // Long enough to cross the P/Q switch at 4096 bytes.
+        let splits: Vec<usize> = (0..200).map(|_| rng.gen_range(0..70)).collect();
+
+        let mut cipher = Hc256::new(&k, &iv);
+        differential_check(&k, &iv, &splits, Granularity::Word, |buf| cipher.apply_stream(buf)).unwrap();
+
+        let mut cipher = BufHc256::new(&k, &iv);
+        differential_check(&k, &iv, &splits, Granularity::Byte, |buf| cipher.apply_stream(buf)).unwrap();
+
+        let mut cipher = PrefetchingHc256::with_capacity(&k, &iv, 64).unwrap();
+        differential_check(&k, &iv, &splits, Granularity::Byte, |buf| cipher.apply_stream(buf)).unwrap();
+    }
+}
+
+#[test]
+fn reports_mismatch() {
+    let k = [1; 32];
+    let iv = [2; 32];
+    let mut cipher = BufHc256::new(&k, &iv);
+
+    // Byte-granular output checked as if every call started on a new word.
+    let result = differential_check(&k, &iv, &[4, 3, 2], Granularity::Word, |buf| cipher.apply_stream(buf));
+    let mismatch: Mismatch = result.unwrap_err();
+    assert_eq!(mismatch.call, 2);
+    assert_eq!(mismatch.offset, 7);
+}
@@ -0,0 +1,110 @@
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use hc256::seekable::{SeekableReader, SeekableWriter, GEN_LEN, HEADER_LEN};
+
+const K: [u8; 32] = [1; 32];
+const IV: [u8; 32] = [2; 32];
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+fn pack(plain: &[u8], chunk_size: u32) -> Vec<u8> {
+    let mut writer = SeekableWriter::create(Cursor::new(Vec::new()), &K, &IV, chunk_size).unwrap();
+    writer.write_all(plain).unwrap();
+    writer.finish().unwrap().into_inner()
+}
+
+#[test]
+fn round_trip() {
+    for len in [0, 1, 100, 256, 1000] {
+        let plain = data(len);
+        let mut reader = SeekableReader::new(Cursor::new(pack(&plain, 256)), &K, &IV).unwrap();
+        assert_eq!(reader.len(), len as u64);
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(out, plain);
+    }
+}
+
+#[test]
+fn random_access() {
+    let plain = data(10_000);
+    let mut reader = SeekableReader::new(Cursor::new(pack(&plain, 512)), &K, &IV).unwrap();
+
+    for offset in [9_999, 0, 4_000, 511, 512, 7_777] {
+        let mut buf = [0; 300];
+        reader.seek(SeekFrom::Start(offset)).unwrap();
+        let n = reader.read(&mut buf).unwrap();
+        assert!(n > 0);
+        assert_eq!(buf[..n], plain[offset as usize..offset as usize + n]);
+    }
+
+    reader.seek(SeekFrom::End(-10)).unwrap();
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, plain[plain.len() - 10..]);
+}
+
+#[test]
+fn append_and_rewrite() {
+    let mut plain = data(700);
+    let file = Cursor::new(pack(&plain, 256));
+
+    let mut writer = SeekableWriter::open(file, &K, &IV).unwrap();
+    writer.append(&[0xee; 300]).unwrap();
+    writer.rewrite_chunk(1, &[0x11; 256]).unwrap();
+    assert_eq!(writer.rewrite_chunk(0, &[0; 10]).unwrap_err().kind(), ErrorKind::InvalidInput);
+    let file = writer.finish().unwrap();
+
+    plain.extend_from_slice(&[0xee; 300]);
+    plain[256..512].copy_from_slice(&[0x11; 256]);
+
+    let mut reader = SeekableReader::new(file, &K, &IV).unwrap();
+    assert_eq!(reader.chunk_count(), 4);
+    let mut out = Vec::new();
+    reader.read_to_end(&mut out).unwrap();
+    assert_eq!(out, plain);
+}
+
+#[test]
+fn rewritten_chunk_uses_new_keystream() {
+    let mut writer = SeekableWriter::create(Cursor::new(Vec::new()), &K, &IV, 64).unwrap();
+    writer.append(&[0; 128]).unwrap();
+    let before = writer.finish().unwrap().into_inner();
+
+    let mut writer = SeekableWriter::open(Cursor::new(before.clone()), &K, &IV).unwrap();
+    writer.rewrite_chunk(0, &[0; 64]).unwrap();
+    let after = writer.finish().unwrap().into_inner();
+
+    let ct = HEADER_LEN + GEN_LEN..HEADER_LEN + GEN_LEN + 64;
+    assert_ne!(before[ct.clone()], after[ct]);
+}
+
+#[test]
+fn tampering_and_truncation_are_detected() {
+    let packed = pack(&data(1000), 256);
+    let stride = GEN_LEN + 256 + 16;
+
+    let mut flipped = packed.clone();
+    flipped[HEADER_LEN + stride + 20] ^= 1;
+    let mut reader = SeekableReader::new(Cursor::new(flipped), &K, &IV).unwrap();
+    reader.seek(SeekFrom::Start(300)).unwrap();
+    assert_eq!(reader.read(&mut [0; 10]).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    let truncated = packed[..HEADER_LEN + 2 * stride].to_vec();
+    let mut reader = SeekableReader::new(Cursor::new(truncated), &K, &IV).unwrap();
+    reader.seek(SeekFrom::Start(100)).unwrap();
+    reader.read_exact(&mut [0; 10]).unwrap();
+    reader.seek(SeekFrom::Start(300)).unwrap();
+    assert_eq!(reader.read(&mut [0; 1]).unwrap_err().kind(), ErrorKind::InvalidData);
+
+    assert!(SeekableReader::new(Cursor::new(packed), &K, &[3; 32]).unwrap().read(&mut [0; 1]).is_err());
+}
+
+#[test]
+fn create_refuses_existing_data() {
+    let err = SeekableWriter::create(Cursor::new(vec![0; 10]), &K, &IV, 64).err().unwrap();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,19 @@
+use hc256::{self_test, Hc256, SelfTestError};
+
+#[test]
+fn passes() {
+    assert_eq!(self_test(), Ok(()));
+}
+
+#[test]
+fn error_reports_first_mismatch() {
+    let e = SelfTestError { test: "vector 1", expected: vec![1, 2, 3], actual: vec![1, 2, 4] };
+    assert_eq!(e.to_string(), "HC-256 self test vector 1 failed at byte 2");
+}
+
+#[test]
+fn new_after_power_on() {
+    let mut result = [0; 4];
+    Hc256::new(&[0; 32], &[0; 32]).apply_stream(&mut result);
+    assert_eq!(result, [0x5b, 0x07, 0x89, 0x85]);
+}
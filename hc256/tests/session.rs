@@ -0,0 +1,74 @@
+use hc256::session::{Opener, Sealer, SessionError, OVERHEAD, WINDOW_SIZE};
+
+#[test]
+fn round_trip() {
+    let k = [7; 32];
+    let iv = [9; 32];
+    let mut sealer = Sealer::new(&k, &iv);
+    let mut opener = Opener::new(&k, &iv);
+
+    for msg in [&b"hello"[..], &b""[..], &[0xaa; 1027][..]] {
+        let frame = sealer.seal(msg).unwrap();
+        assert_eq!(frame.len(), msg.len() + OVERHEAD);
+        assert_eq!(opener.open(&frame).unwrap(), msg);
+    }
+    assert_eq!(sealer.sequence(), 3);
+}
+
+#[test]
+fn messages_use_distinct_keystream() {
+    let mut sealer = Sealer::new(&[1; 32], &[2; 32]);
+    let a = sealer.seal(&[0; 32]).unwrap();
+    let b = sealer.seal(&[0; 32]).unwrap();
+
+    assert_ne!(a[8..40], b[8..40]);
+}
+
+#[test]
+fn reordered_frames_are_accepted() {
+    let mut sealer = Sealer::new(&[3; 32], &[4; 32]);
+    let mut opener = Opener::new(&[3; 32], &[4; 32]);
+    let frames: Vec<Vec<u8>> = (0..5u8).map(|i| sealer.seal(&[i]).unwrap()).collect();
+
+    for i in [4, 0, 2, 1, 3] {
+        assert_eq!(opener.open(&frames[i]).unwrap(), [i as u8]);
+    }
+}
+
+#[test]
+fn replayed_frame_is_rejected() {
+    let mut sealer = Sealer::new(&[3; 32], &[4; 32]);
+    let mut opener = Opener::new(&[3; 32], &[4; 32]);
+    let frame = sealer.seal(b"once").unwrap();
+
+    opener.open(&frame).unwrap();
+    assert_eq!(opener.open(&frame), Err(SessionError::Replayed(0)));
+}
+
+#[test]
+fn old_frame_is_rejected() {
+    let mut sealer = Sealer::new(&[5; 32], &[6; 32]);
+    let mut opener = Opener::new(&[5; 32], &[6; 32]);
+    let first = sealer.seal(b"first").unwrap();
+
+    for _ in 0..WINDOW_SIZE {
+        opener.open(&sealer.seal(b"x").unwrap()).unwrap();
+    }
+    assert_eq!(opener.open(&first), Err(SessionError::TooOld(0)));
+}
+
+#[test]
+fn tampered_frame_is_rejected() {
+    let mut sealer = Sealer::new(&[8; 32], &[8; 32]);
+    let mut opener = Opener::new(&[8; 32], &[8; 32]);
+    let mut frame = sealer.seal(b"payload").unwrap();
+    frame[10] ^= 1;
+
+    assert_eq!(opener.open(&frame), Err(SessionError::Authentication));
+    assert_eq!(opener.open(&frame[..OVERHEAD - 1]), Err(SessionError::Truncated));
+
+    let mut wrong_iv = Opener::new(&[8; 32], &[9; 32]);
+    frame[10] ^= 1;
+    assert_eq!(wrong_iv.open(&frame), Err(SessionError::Authentication));
+    assert_eq!(opener.open(&frame).unwrap(), b"payload");
+}
@@ -0,0 +1,96 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use hc256::stream::{SecureStream, MAX_RECORD};
+
+fn pair(client_key: [u8; 32], server_key: [u8; 32]) -> (std::io::Result<SecureStream<TcpStream>>, thread::JoinHandle<std::io::Result<SecureStream<TcpStream>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (socket, _) = listener.accept().unwrap();
+        SecureStream::accept(socket, &server_key)
+    });
+
+    let client = SecureStream::connect(TcpStream::connect(addr).unwrap(), &client_key);
+    (client, server)
+}
+
+#[test]
+fn echo_over_localhost() {
+    let (client, server) = pair([1; 32], [1; 32]);
+    let mut client = client.unwrap();
+
+    let echo = thread::spawn(move || {
+        let mut server = server.join().unwrap().unwrap();
+        let mut data = Vec::new();
+        server.read_to_end(&mut data).unwrap();
+        server.write_all(&data).unwrap();
+        server.finish().unwrap();
+    });
+
+    let msg: Vec<u8> = (0..(3 * MAX_RECORD + 17)).map(|i| i as u8).collect();
+    client.write_all(&msg).unwrap();
+    client.finish().unwrap();
+    client.get_ref().shutdown(std::net::Shutdown::Write).unwrap();
+
+    let mut reply = Vec::new();
+    client.read_to_end(&mut reply).unwrap();
+    echo.join().unwrap();
+
+    assert_eq!(reply, msg);
+}
+
+#[test]
+fn ciphertext_differs_from_plaintext() {
+    let (client, server) = pair([2; 32], [2; 32]);
+    let mut client = client.unwrap();
+    let server = server.join().unwrap().unwrap();
+    let mut raw = server.into_inner();
+
+    client.write_all(&[0; 64]).unwrap();
+
+    let mut record = [0; 2 + 64 + 16];
+    raw.read_exact(&mut record).unwrap();
+    assert_eq!(&record[..2], &64u16.to_le_bytes());
+    assert_ne!(&record[2..66], &[0; 64][..]);
+}
+
+#[test]
+fn mismatched_keys_fail_handshake() {
+    let (client, server) = pair([3; 32], [4; 32]);
+
+    assert_eq!(client.err().unwrap().kind(), ErrorKind::PermissionDenied);
+    assert_eq!(server.join().unwrap().err().unwrap().kind(), ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn missing_final_record_is_truncation() {
+    let (client, server) = pair([5; 32], [5; 32]);
+    let mut client = client.unwrap();
+    let mut server = server.join().unwrap().unwrap();
+
+    client.write_all(&[7; 100]).unwrap();
+    client.get_ref().shutdown(std::net::Shutdown::Write).unwrap();
+
+    let mut data = Vec::new();
+    assert_eq!(server.read_to_end(&mut data).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(data, [7; 100]);
+}
+
+#[test]
+fn write_after_finish_fails() {
+    let (client, server) = pair([6; 32], [6; 32]);
+    let mut client = client.unwrap();
+    let mut server = server.join().unwrap().unwrap();
+
+    client.write_all(b"last").unwrap();
+    client.finish().unwrap();
+    assert_eq!(client.write(b"more").unwrap_err().kind(), ErrorKind::BrokenPipe);
+
+    let mut buf = [0; 8];
+    assert_eq!(server.read(&mut buf).unwrap(), 4);
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+}
@@ -76,6 +76,62 @@ fn initialize_time() {
     file.write_all(&info_string.as_bytes()).unwrap();
 }
 
+#[test]
+fn set_state_time() {
+    let mut keys: [[u8; 32]; 2048] = [[0; 32]; 2048];
+    let mut ivs: [[u8; 32]; 2048] = [[0; 32]; 2048];
+
+    for i in 0..2048 {
+        rand::thread_rng().fill_bytes(&mut keys[i]);
+        rand::thread_rng().fill_bytes(&mut ivs[i]);
+    }
+
+    let mut cipher = Hc256::new(&keys[0], &ivs[0]);
+
+    let mut shortest = u128::MAX;
+    let mut longest = u128::MIN;
+    let mut init_sum = 0;
+
+    // Warmup
+    for i in 0..2048 {
+        cipher.set_state(&keys[i], &ivs[i], 0);
+    }
+
+    // Run test
+    let total_time = Instant::now();
+    for i in 0..2048 {
+        let init_time = Instant::now();
+        cipher.set_state(&keys[i], &ivs[i], 0);
+        let elapsed = init_time.elapsed().as_nanos();
+
+        if elapsed > longest {
+            longest = elapsed;
+        }
+
+        if elapsed < shortest {
+            shortest = elapsed;
+        }
+
+        init_sum += elapsed;
+    }
+    let total_time = total_time.elapsed().as_nanos();
+    let avg_init = init_sum as f64 / 2048.0;
+
+    let info_string = format!(
+        "-------------------------------------\nData\n-------------------------------------\nTotal time:           {}\nTotal re-keys:        {}\nAverage re-key time:  {}\nFastest re-key time:  {}\nSlowest re-key time:  {}\nRe-keys per second:   {}\n-------------------------------------\n"
+        , total_time
+        , init_sum
+        , avg_init
+        , shortest
+        , longest
+        , 1e9 / avg_init
+    );
+
+    std::fs::create_dir_all("timing").unwrap();
+    let mut file = File::create("timing/set-state-time-info-2048").unwrap();
+    file.write_all(info_string.as_bytes()).unwrap();
+}
+
 #[test]
 fn apply_stream_time() {
     let key: [u8; 32] = [0; 32];